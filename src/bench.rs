@@ -0,0 +1,20 @@
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+// the result of `f` and how long it took
+pub(crate) fn time<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let instant = Instant::now();
+    let res = black_box(f());
+    (res, instant.elapsed())
+}
+
+// `items` of `unit` handled in `elapsed`
+pub(crate) fn report(label: &str, items: u64, unit: &str, elapsed: Duration) {
+    let per_second = items as f64 / elapsed.as_secs_f64();
+    println!(
+        "{label}: {} µs ({per_second:.0} {unit} per second)",
+        elapsed.as_micros()
+    );
+}
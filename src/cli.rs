@@ -0,0 +1,37 @@
+use std::{fmt::Display, str::FromStr};
+
+// the value following option `name`, parsed; the error is ready to print
+pub(crate) fn option_value<'a, T: FromStr>(
+    args: &mut impl Iterator<Item = &'a String>,
+    name: &str,
+) -> Result<T, String>
+where
+    T::Err: Display,
+{
+    let val = args
+        .next()
+        .ok_or_else(|| format!("{name} expects a value"))?;
+    val.parse()
+        .map_err(|e| format!("{name}: could not parse {val}: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_option_value() {
+        let args: Vec<String> = ["12", "x"].map(String::from).into();
+        let mut args = args.iter();
+
+        assert_eq!(option_value::<u64>(&mut args, "--n"), Ok(12));
+        assert_eq!(
+            option_value::<u64>(&mut args, "--n"),
+            Err("--n: could not parse x: invalid digit found in string".to_string())
+        );
+        assert_eq!(
+            option_value::<u64>(&mut args, "--n"),
+            Err("--n expects a value".to_string())
+        );
+    }
+}
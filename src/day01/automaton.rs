@@ -0,0 +1,227 @@
+// Aho-Corasick over bytes: a trie of the patterns whose missing edges are
+// filled in from the failure links, so every byte is a single table lookup.
+
+const ROOT: usize = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct Match {
+    // byte offsets into the line, end exclusive
+    pub(super) start: usize,
+    pub(super) end: usize,
+    pub(super) value: u8,
+}
+
+pub(super) struct Automaton {
+    // patterns were inserted folded by `fold`, and input is folded the same
+    // way as it is read
+    ignore_case: bool,
+    goto: Vec<[u32; 256]>,
+    // (value, pattern length) of the pattern ending at each node
+    output: Vec<Option<(u8, usize)>>,
+    // nearest node on the failure chain that has an output
+    dict: Vec<Option<usize>>,
+}
+impl Automaton {
+    pub(super) fn new(patterns: &[(&str, u8)], ignore_case: bool) -> Self {
+        let mut automaton = Automaton {
+            ignore_case,
+            goto: vec![[u32::MAX; 256]],
+            output: vec![None],
+            dict: vec![None],
+        };
+        for (pattern, value) in patterns {
+            let mut node = ROOT;
+            let folded: String = match ignore_case {
+                true => pattern.chars().map(fold).collect(),
+                false => pattern.to_string(),
+            };
+            for &b in folded.as_bytes() {
+                node = match automaton.goto[node][b as usize] {
+                    u32::MAX => {
+                        automaton.goto.push([u32::MAX; 256]);
+                        automaton.output.push(None);
+                        automaton.dict.push(None);
+                        let next = automaton.goto.len() - 1;
+                        automaton.goto[node][b as usize] = next as u32;
+                        next
+                    }
+                    next => next as usize,
+                };
+            }
+            // the first of duplicate patterns wins
+            automaton.output[node].get_or_insert((*value, pattern.len()));
+        }
+        automaton.link();
+        automaton
+    }
+
+    // breadth first, so a node's failure target is finished before the node
+    fn link(&mut self) {
+        let mut fail = vec![ROOT; self.goto.len()];
+        let mut queue = std::collections::VecDeque::new();
+        for b in 0..256 {
+            match self.goto[ROOT][b] {
+                u32::MAX => self.goto[ROOT][b] = ROOT as u32,
+                child => queue.push_back(child as usize),
+            }
+        }
+        while let Some(node) = queue.pop_front() {
+            for b in 0..256 {
+                let target = self.goto[fail[node]][b];
+                match self.goto[node][b] {
+                    u32::MAX => self.goto[node][b] = target,
+                    child => {
+                        let child = child as usize;
+                        fail[child] = target as usize;
+                        self.dict[child] = if self.output[fail[child]].is_some() {
+                            Some(fail[child])
+                        } else {
+                            self.dict[fail[child]]
+                        };
+                        queue.push_back(child);
+                    }
+                }
+            }
+        }
+    }
+
+    // the matches with the lowest and highest start, found in one pass;
+    // overlapping matches ("eightwo") are all reported
+    pub(super) fn first_and_last(&self, line: &str) -> Option<(Match, Match)> {
+        let mut scan = Scan {
+            state: ROOT,
+            first: None,
+            last: None,
+        };
+        if self.ignore_case {
+            // `fold` keeps every character's length, so offsets still
+            // point into `line`
+            let mut buf = [0; 4];
+            for (start, c) in line.char_indices() {
+                let folded = fold(c).encode_utf8(&mut buf);
+                for (offset, &b) in folded.as_bytes().iter().enumerate() {
+                    scan.step(self, start + offset, b);
+                }
+            }
+        } else {
+            for (idx, &b) in line.as_bytes().iter().enumerate() {
+                scan.step(self, idx, b);
+            }
+        }
+        Some((scan.first?, scan.last?))
+    }
+
+    pub(super) fn calibration_value(&self, line: &str) -> Option<u32> {
+        let (first, last) = self.first_and_last(line)?;
+        Some(u32::from(first.value) * 10 + u32::from(last.value))
+    }
+}
+
+struct Scan {
+    state: usize,
+    first: Option<Match>,
+    last: Option<Match>,
+}
+impl Scan {
+    fn step(&mut self, automaton: &Automaton, idx: usize, b: u8) {
+        self.state = automaton.goto[self.state][b as usize] as usize;
+        let mut node = if automaton.output[self.state].is_some() {
+            Some(self.state)
+        } else {
+            automaton.dict[self.state]
+        };
+        while let Some(current) = node {
+            let (value, len) = automaton.output[current].unwrap();
+            let found = Match {
+                start: idx + 1 - len,
+                end: idx + 1,
+                value,
+            };
+            if self.first.is_none_or(|m| found.start < m.start) {
+                self.first = Some(found);
+            }
+            if self.last.is_none_or(|m| found.start >= m.start) {
+                self.last = Some(found);
+            }
+            node = automaton.dict[current];
+        }
+    }
+}
+
+// lower case, for letters whose lower case form is a single character of
+// the same encoded length; anything else is matched as written
+fn fold(c: char) -> char {
+    if c.is_ascii() {
+        return c.to_ascii_lowercase();
+    }
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(l), None) if l.len_utf8() == c.len_utf8() => l,
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day01::CONVERSION_TABLE;
+
+    #[test]
+    fn test_overlapping_words() {
+        let automaton = Automaton::new(&CONVERSION_TABLE, false);
+        let values = |line| {
+            automaton
+                .first_and_last(line)
+                .map(|(first, last)| (first.value, last.value))
+        };
+
+        assert_eq!(values("eightwo"), Some((8, 2)));
+        assert_eq!(values("xtwone3four"), Some((2, 4)));
+        assert_eq!(values("oneight"), Some((1, 8)));
+        assert_eq!(values("sevenine"), Some((7, 9)));
+        assert_eq!(values("abcseven"), Some((7, 7)));
+        assert_eq!(values("onfive"), Some((5, 5)));
+        assert_eq!(values("nothing"), None);
+        assert_eq!(
+            automaton.first_and_last("ab1cdtwo"),
+            Some((
+                Match {
+                    start: 2,
+                    end: 3,
+                    value: 1
+                },
+                Match {
+                    start: 5,
+                    end: 8,
+                    value: 2
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_ignore_case_folds_input() {
+        let automaton = Automaton::new(&[("Åtte", 8), ("ni", 9)], true);
+        let (first, last) = automaton.first_and_last("xÅTTEnI").unwrap();
+
+        assert_eq!((first.start, first.end, first.value), (1, 6, 8));
+        assert_eq!((last.start, last.end, last.value), (6, 8, 9));
+        // a case sensitive automaton matches exactly as written
+        let automaton = Automaton::new(&[("Åtte", 8)], false);
+        assert_eq!(automaton.first_and_last("åtte"), None);
+        // the Kelvin sign folds to a shorter 'k', so it is left alone
+        let automaton = Automaton::new(&[("k", 1)], true);
+        assert_eq!(automaton.first_and_last("\u{212a}"), None);
+        assert!(automaton.first_and_last("K").is_some());
+    }
+
+    #[test]
+    fn test_pattern_inside_later_pattern() {
+        // "bc" ends before "abcd" does but starts after it
+        let automaton = Automaton::new(&[("abcd", 1), ("bc", 2), ("d", 3)], false);
+        let (first, last) = automaton.first_and_last("xabcd").unwrap();
+
+        assert_eq!((first.start, first.end, first.value), (1, 5, 1));
+        assert_eq!((last.start, last.end, last.value), (4, 5, 3));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::bench::{report, time};
+
+use super::{
+    generator::{generate_document, GeneratorConfig},
+    get_line_sum_part_one, get_line_sum_part_two, parse_lines, CONVERSION_TABLE, NUMBERS,
+};
+
+const DEFAULT_LINES: u64 = 200_000;
+
+// `--bench [lines]`: a generated document of `lines` lines
+pub(super) fn run(args: &[String]) {
+    let lines = match args.first().map(|arg| (arg, arg.parse())) {
+        None => DEFAULT_LINES,
+        Some((_, Ok(lines))) => lines,
+        Some((arg, Err(e))) => {
+            eprintln!("could not parse {arg}: {e}");
+            return;
+        }
+    };
+    let document = generate_document(&GeneratorConfig {
+        lines,
+        ..Default::default()
+    })
+    .text;
+    println!("{lines} lines, {} bytes", document.len());
+
+    let (collected, collected_time) = time(|| parse_lines(&document, collect_digits_line_sum));
+    let (scanned, scanned_time) = time(|| parse_lines(&document, get_line_sum_part_one));
+    assert_eq!(collected, scanned);
+    report(
+        "digits, collect every digit",
+        lines,
+        "lines",
+        collected_time,
+    );
+    report("digits, bidirectional scan", lines, "lines", scanned_time);
+
+    let (indexed, indexed_time) = time(|| parse_lines(&document, match_indices_line_sum));
+    let (automaton, automaton_time) = time(|| parse_lines(&document, automaton_line_sum));
+    let (scanned, scanned_time) = time(|| parse_lines(&document, get_line_sum_part_two));
+    assert_eq!(indexed, automaton);
+    assert_eq!(indexed, scanned);
+    report(
+        "spelled digits, match_indices per word",
+        lines,
+        "lines",
+        indexed_time,
+    );
+    report("spelled digits, automaton", lines, "lines", automaton_time);
+    report(
+        "spelled digits, bidirectional scan",
+        lines,
+        "lines",
+        scanned_time,
+    );
+}
+
+// part one as it was before the bidirectional scan
+fn collect_digits_line_sum(line: &str) -> Option<u32> {
+    let digits: Vec<_> = line.chars().filter_map(|c| c.to_digit(10)).collect();
+    Some(*digits.first()? * 10 + *digits.last()?)
+}
+
+// part two before the automaton: every word searched separately
+fn match_indices_line_sum(line: &str) -> Option<u32> {
+    let mut matches = Vec::new();
+    for (number_str, val) in CONVERSION_TABLE {
+        for (idx, _) in line.match_indices(number_str) {
+            matches.push((idx, val));
+        }
+    }
+    matches.sort_by_key(|(idx, _)| *idx);
+    Some((matches.first()?.1 * 10 + matches.last()?.1) as u32)
+}
+
+fn automaton_line_sum(line: &str) -> Option<u32> {
+    NUMBERS.calibration_value(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_sums_agree() {
+        let document = generate_document(&GeneratorConfig {
+            seed: 7,
+            lines: 2_000,
+            ..Default::default()
+        })
+        .text;
+        for line in document
+            .lines()
+            .chain(["eightwo", "twone", "oneighthree", "nineight", "xyz"])
+        {
+            assert_eq!(
+                get_line_sum_part_one(line),
+                collect_digits_line_sum(line),
+                "{line}"
+            );
+            assert_eq!(
+                get_line_sum_part_two(line),
+                match_indices_line_sum(line),
+                "{line}"
+            );
+            assert_eq!(
+                automaton_line_sum(line),
+                match_indices_line_sum(line),
+                "{line}"
+            );
+        }
+    }
+}
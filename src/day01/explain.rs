@@ -0,0 +1,234 @@
+use std::{
+    fs,
+    io::{self, Read},
+};
+
+use super::{automaton::Match, scan};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum TokenKind {
+    Digit,
+    Word,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct Span<'a> {
+    // byte offsets into the line, end exclusive
+    pub(super) start: usize,
+    pub(super) end: usize,
+    pub(super) text: &'a str,
+    pub(super) kind: TokenKind,
+    pub(super) value: u8,
+}
+
+// the two tokens a calibration value is made of; they are the same token
+// when a line holds only one, and may overlap ("eightwo")
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct Explanation<'a> {
+    pub(super) first: Span<'a>,
+    pub(super) last: Span<'a>,
+}
+impl Explanation<'_> {
+    pub(super) fn value(&self) -> u32 {
+        u32::from(self.first.value) * 10 + u32::from(self.last.value)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) struct LineExplanation<'a> {
+    // 1-based
+    pub(super) line: usize,
+    pub(super) text: &'a str,
+    // `None` for a line without any token
+    pub(super) tokens: Option<Explanation<'a>>,
+}
+
+// `--explain [--words] [path]`: reads the file at `path`, or stdin, and marks
+// the first (^) and last (~) token of every line
+pub(super) fn run(args: &[String]) {
+    let mut words = false;
+    let mut path = None;
+    for arg in args {
+        match arg.as_str() {
+            "--words" => words = true,
+            other if path.is_none() && !other.starts_with("--") => path = Some(other),
+            other => {
+                eprintln!("unknown explain option: {other}");
+                return;
+            }
+        }
+    }
+
+    let document = match path {
+        Some(path) => fs::read_to_string(path).map_err(|e| format!("could not read {path}: {e}")),
+        None => {
+            let mut document = String::new();
+            io::stdin()
+                .read_to_string(&mut document)
+                .map(|_| document)
+                .map_err(|e| format!("could not read stdin: {e}"))
+        }
+    };
+    let document = match document {
+        Ok(document) => document,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    for line in explain(&document, words) {
+        print!("{}", highlight(&line));
+    }
+}
+
+pub(super) fn explain(document: &str, words: bool) -> Vec<LineExplanation<'_>> {
+    let explain_line = if words { digits_and_words } else { digits };
+    document
+        .lines()
+        .enumerate()
+        .map(|(idx, text)| LineExplanation {
+            line: idx + 1,
+            text,
+            tokens: explain_line(text),
+        })
+        .collect()
+}
+
+// part one: digit characters only
+pub(super) fn digits(line: &str) -> Option<Explanation<'_>> {
+    scan::digits(line).map(|tokens| explanation(line, tokens))
+}
+
+// part two: digits and spelled-out English words
+pub(super) fn digits_and_words(line: &str) -> Option<Explanation<'_>> {
+    scan::digits_and_words(line).map(|tokens| explanation(line, tokens))
+}
+
+// the tokens are found by the same scan `get_line_sum_part_one` and
+// `get_line_sum_part_two` use, so an explanation always agrees with the sums
+fn explanation(line: &str, (first, last): (Match, Match)) -> Explanation<'_> {
+    let span = |token: Match| {
+        let text = &line[token.start..token.end];
+        let kind = if text.as_bytes()[0].is_ascii_digit() {
+            TokenKind::Digit
+        } else {
+            TokenKind::Word
+        };
+        Span {
+            start: token.start,
+            end: token.end,
+            text,
+            kind,
+            value: token.value,
+        }
+    };
+    Explanation {
+        first: span(first),
+        last: span(last),
+    }
+}
+
+// the line, a marker row underneath, and a description of both tokens
+pub(super) fn highlight(line: &LineExplanation) -> String {
+    let prefix = format!("{:>5} | ", line.line);
+    let Some(tokens) = line.tokens else {
+        return format!("{prefix}{}\n{:>5} | no token\n", line.text, "");
+    };
+
+    let mut markers = String::new();
+    for (offset, _) in line.text.char_indices() {
+        let in_first = (tokens.first.start..tokens.first.end).contains(&offset);
+        let in_last = (tokens.last.start..tokens.last.end).contains(&offset);
+        markers.push(match (in_first, in_last) {
+            (true, true) => '*',
+            (true, false) => '^',
+            (false, true) => '~',
+            (false, false) => ' ',
+        });
+    }
+    let describe = |span: &Span| {
+        let kind = match span.kind {
+            TokenKind::Digit => "digit",
+            TokenKind::Word => "word",
+        };
+        format!("{kind} {:?} at {}..{}", span.text, span.start, span.end)
+    };
+    format!(
+        "{prefix}{}\n{:>5} | {}\n{:>5} | {} = first {}, last {}\n",
+        line.text,
+        "",
+        markers.trim_end(),
+        "",
+        tokens.value(),
+        describe(&tokens.first),
+        describe(&tokens.last)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_digits() {
+        let explanation = digits("pqr3stu8vwx").unwrap();
+
+        assert_eq!(explanation.value(), 38);
+        assert_eq!(
+            explanation.first,
+            Span {
+                start: 3,
+                end: 4,
+                text: "3",
+                kind: TokenKind::Digit,
+                value: 3
+            }
+        );
+        assert_eq!(explanation.last.start, 7);
+        // a single digit is both the first and the last token
+        let single = digits("treb7uchet").unwrap();
+        assert_eq!(single.first, single.last);
+        assert_eq!(digits("abc"), None);
+    }
+
+    #[test]
+    fn test_explain_words() {
+        let explanation = digits_and_words("xtwone3four").unwrap();
+
+        assert_eq!(explanation.value(), 24);
+        assert_eq!(
+            (explanation.first.text, explanation.first.kind),
+            ("two", TokenKind::Word)
+        );
+        assert_eq!((explanation.first.start, explanation.first.end), (1, 4));
+        assert_eq!(
+            (
+                explanation.last.text,
+                explanation.last.start,
+                explanation.last.end
+            ),
+            ("four", 7, 11)
+        );
+    }
+
+    #[test]
+    fn test_explain_document() {
+        let lines = explain("two1nine\nnothing\n", true);
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].tokens.unwrap().value(), 29);
+        assert_eq!(lines[1].line, 2);
+        assert_eq!(lines[1].tokens, None);
+    }
+
+    #[test]
+    fn test_highlight() {
+        let lines = explain("eightwo\nzzz\n", true);
+
+        assert_eq!(
+            highlight(&lines[0]),
+            "    1 | eightwo\n      | ^^^^*~~\n      | 82 = first word \"eight\" at 0..5, last word \"two\" at 4..7\n"
+        );
+        assert_eq!(highlight(&lines[1]), "    2 | zzz\n      | no token\n");
+    }
+}
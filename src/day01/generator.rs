@@ -0,0 +1,238 @@
+use super::DIGIT_WORDS;
+use crate::{cli::option_value, rng::Rng};
+
+// none of these letters occur in a number word, so noise can never complete
+// a word, and keeping pieces apart with noise means no word straddles two
+const NOISE: &[u8] = b"abcdjklmpqyz";
+// words sharing letters, with the values they contain in order of start
+const OVERLAPS: [(&str, [u8; 2]); 8] = [
+    ("oneight", [1, 8]),
+    ("twone", [2, 1]),
+    ("eightwo", [8, 2]),
+    ("eighthree", [8, 3]),
+    ("nineight", [9, 8]),
+    ("sevenine", [7, 9]),
+    ("fiveight", [5, 8]),
+    ("threeight", [3, 8]),
+];
+
+#[derive(Debug, Clone, Copy)]
+pub(super) struct GeneratorConfig {
+    pub(super) seed: u64,
+    pub(super) lines: u64,
+    // tokens (digits, words or overlapping words) per line, at least one
+    pub(super) max_pieces: u64,
+    pub(super) max_noise: u64,
+    // out of 100: lines with a single token, and pieces that are overlaps
+    pub(super) single_token_percent: u64,
+    pub(super) overlap_percent: u64,
+}
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        GeneratorConfig {
+            seed: 2023,
+            lines: 1000,
+            max_pieces: 6,
+            max_noise: 6,
+            single_token_percent: 10,
+            overlap_percent: 15,
+        }
+    }
+}
+
+// the document together with the value each line is expected to give;
+// `None` marks a line without any digit (part one) or token (part two)
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct GeneratedDocument {
+    pub(super) text: String,
+    pub(super) part_one: Vec<Option<u32>>,
+    pub(super) part_two: Vec<Option<u32>>,
+}
+impl GeneratedDocument {
+    pub(super) fn sum_one(&self) -> u64 {
+        self.part_one.iter().flatten().map(|v| u64::from(*v)).sum()
+    }
+
+    pub(super) fn sum_two(&self) -> u64 {
+        self.part_two.iter().flatten().map(|v| u64::from(*v)).sum()
+    }
+}
+
+// `--generate [--seed N] [--lines N] [--max-pieces N] [--max-noise N]
+// [--single N] [--overlaps N]`: the document goes to stdout, the expected
+// answers to stderr
+pub(super) fn run(args: &[String]) {
+    let mut config = GeneratorConfig::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let parsed = match arg.as_str() {
+            "--seed" => option_value(&mut args, arg).map(|val| config.seed = val),
+            "--lines" => option_value(&mut args, arg).map(|val| config.lines = val),
+            "--max-pieces" => option_value(&mut args, arg).map(|val| config.max_pieces = val),
+            "--max-noise" => option_value(&mut args, arg).map(|val| config.max_noise = val),
+            "--single" => option_value(&mut args, arg).map(|val| config.single_token_percent = val),
+            "--overlaps" => option_value(&mut args, arg).map(|val| config.overlap_percent = val),
+            other => Err(format!("unknown generator option: {other}")),
+        };
+        if let Err(e) = parsed {
+            eprintln!("{e}");
+            return;
+        }
+    }
+    let document = generate_document(&config);
+    print!("{}", document.text);
+    eprintln!("part one: {}", document.sum_one());
+    eprintln!("part two: {}", document.sum_two());
+}
+
+pub(super) fn generate_document(config: &GeneratorConfig) -> GeneratedDocument {
+    let mut rng = Rng::new(config.seed);
+    let mut document = GeneratedDocument {
+        text: String::new(),
+        part_one: Vec::new(),
+        part_two: Vec::new(),
+    };
+    for _ in 0..config.lines {
+        let pieces = if rng.gen_range(0..100) < config.single_token_percent {
+            1
+        } else {
+            rng.gen_range_inclusive(1..=config.max_pieces.max(1))
+        };
+        let single = pieces == 1;
+
+        // values in order of start, flagged when they come from a digit
+        let mut tokens: Vec<(u8, bool)> = Vec::new();
+        push_noise(&mut rng, &mut document.text, 0, config.max_noise);
+        for piece in 0..pieces {
+            if piece > 0 {
+                push_noise(&mut rng, &mut document.text, 1, config.max_noise.max(1));
+            }
+            let value = rng.gen_range_inclusive(1..=9) as u8;
+            match rng.gen_range(0..100) {
+                // an overlap holds two tokens, so never on a single token line
+                roll if roll < config.overlap_percent && !single => {
+                    let (text, values) = OVERLAPS[rng.gen_range(0..OVERLAPS.len() as u64) as usize];
+                    document.text += text;
+                    tokens.extend(values.map(|value| (value, false)));
+                }
+                roll if roll % 2 == 0 => {
+                    document.text.push(char::from(b'0' + value));
+                    tokens.push((value, true));
+                }
+                _ => {
+                    document.text += DIGIT_WORDS[usize::from(value - 1)];
+                    tokens.push((value, false));
+                }
+            }
+        }
+        push_noise(&mut rng, &mut document.text, 0, config.max_noise);
+        document.text.push('\n');
+
+        let digits: Vec<u8> = tokens
+            .iter()
+            .filter(|(_, digit)| *digit)
+            .map(|(value, _)| *value)
+            .collect();
+        document.part_one.push(calibration_value(&digits));
+        let all: Vec<u8> = tokens.iter().map(|(value, _)| *value).collect();
+        document.part_two.push(calibration_value(&all));
+    }
+    document
+}
+
+fn push_noise(rng: &mut Rng, text: &mut String, min: u64, max: u64) {
+    for _ in 0..rng.gen_range_inclusive(min..=max.max(min)) {
+        text.push(char::from(
+            NOISE[rng.gen_range(0..NOISE.len() as u64) as usize],
+        ));
+    }
+}
+
+fn calibration_value(values: &[u8]) -> Option<u32> {
+    Some(u32::from(*values.first()?) * 10 + u32::from(*values.last()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day01::{
+        explain, get_line_sum_part_one, get_line_sum_part_two, parallel::calibrate_parallel,
+        puzzle_one, puzzle_two, NUMBERS,
+    };
+
+    fn large(seed: u64) -> GeneratedDocument {
+        generate_document(&GeneratorConfig {
+            seed,
+            lines: 20_000,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_noise_never_spells_a_word() {
+        for word in DIGIT_WORDS {
+            assert!(!word.bytes().any(|b| NOISE.contains(&b)), "{word}");
+        }
+    }
+
+    #[test]
+    fn test_generator_is_reproducible() {
+        let config = GeneratorConfig::default();
+        assert_eq!(generate_document(&config), generate_document(&config));
+        assert_ne!(
+            generate_document(&config).text,
+            generate_document(&GeneratorConfig { seed: 1, ..config }).text
+        );
+    }
+
+    #[test]
+    fn test_generated_lines_are_tricky() {
+        let document = large(3);
+        let lines: Vec<&str> = document.text.lines().collect();
+
+        assert_eq!(lines.len(), 20_000);
+        assert!(OVERLAPS
+            .iter()
+            .all(|(overlap, _)| lines.iter().any(|line| line.contains(overlap))));
+        // single token lines: first and last are the same token
+        assert!(lines.iter().any(|line| {
+            explain::digits_and_words(line).is_some_and(|tokens| tokens.first == tokens.last)
+        }));
+        assert!(document.part_one.iter().any(Option::is_none));
+    }
+
+    #[test]
+    fn test_puzzles_match_ground_truth() {
+        for seed in [0, 1, 2] {
+            let document = large(seed);
+
+            assert_eq!(puzzle_one(&document.text), document.sum_one());
+            assert_eq!(puzzle_two(&document.text), document.sum_two());
+        }
+    }
+
+    #[test]
+    fn test_line_values_match_ground_truth() {
+        let document = large(4);
+        for ((line, one), two) in document
+            .text
+            .lines()
+            .zip(&document.part_one)
+            .zip(&document.part_two)
+        {
+            assert_eq!(get_line_sum_part_one(line), *one, "{line}");
+            assert_eq!(get_line_sum_part_two(line), *two, "{line}");
+            assert_eq!(NUMBERS.calibration_value(line), *two, "{line}");
+            assert_eq!(
+                explain::digits_and_words(line).map(|e| e.value()),
+                *two,
+                "{line}"
+            );
+        }
+
+        let (calibration, _) =
+            calibrate_parallel(document.text.as_bytes(), 4, 4096, get_line_sum_part_two).unwrap();
+        assert_eq!(calibration.sum, document.sum_two());
+        assert_eq!(calibration.rejected.len(), 0);
+    }
+}
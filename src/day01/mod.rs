@@ -1,3 +1,17 @@
+use std::sync::LazyLock;
+
+mod automaton;
+mod bench;
+mod explain;
+mod generator;
+mod numbers;
+mod parallel;
+mod scan;
+mod stream;
+mod vocabulary;
+
+use automaton::Automaton;
+
 const DOCUMENT: &str = include_str!("calibration_document.txt");
 const CONVERSION_TABLE: [(&str, u8); 18] = [
     ("1", 1),
@@ -19,6 +33,35 @@ const CONVERSION_TABLE: [(&str, u8); 18] = [
     ("eight", 8),
     ("nine", 9),
 ];
+// the spelled-out entries of `CONVERSION_TABLE`, indexed by value - 1
+const DIGIT_WORDS: [&str; 9] = digit_words();
+
+const fn digit_words() -> [&'static str; 9] {
+    let mut words = [""; 9];
+    let mut idx = 0;
+    while idx < CONVERSION_TABLE.len() {
+        let (word, value) = CONVERSION_TABLE[idx];
+        if !word.as_bytes()[0].is_ascii_digit() {
+            words[value as usize - 1] = word;
+        }
+        idx += 1;
+    }
+    words
+}
+
+static NUMBERS: LazyLock<Automaton> = LazyLock::new(|| Automaton::new(&CONVERSION_TABLE, false));
+
+pub(crate) fn run(args: &[String]) {
+    match args.first().map(String::as_str) {
+        None => main(),
+        Some("--stream") => stream::run(&args[1..]),
+        Some("--bench") => bench::run(&args[1..]),
+        Some("--explain") => explain::run(&args[1..]),
+        Some("--parallel") => parallel::run(&args[1..]),
+        Some("--generate") => generator::run(&args[1..]),
+        Some(other) => eprintln!("unknown day01 option: {other}"),
+    }
+}
 
 pub fn main() {
     let sum = puzzle_one(DOCUMENT);
@@ -27,39 +70,45 @@ pub fn main() {
     println!("part two: {sum}");
 }
 
-fn puzzle_two(document: &str) -> u32 {
+fn puzzle_two(document: &str) -> u64 {
     parse_lines(document, get_line_sum_part_two)
 }
 
-fn puzzle_one(document: &str) -> u32 {
+fn puzzle_one(document: &str) -> u64 {
     parse_lines(document, get_line_sum_part_one)
 }
 
-fn parse_lines<F: Fn(&str) -> u32>(document: &str, sum_first_and_last: F) -> u32 {
-    document.lines().map(sum_first_and_last).sum()
+// lines without any digit contribute nothing; `stream::calibrate` reports them
+fn parse_lines<F: Fn(&str) -> Option<u32>>(document: &str, sum_first_and_last: F) -> u64 {
+    document
+        .lines()
+        .filter_map(sum_first_and_last)
+        .map(u64::from)
+        .sum()
 }
 
-fn get_line_sum_part_one(line: &str) -> u32 {
-    let digits: Vec<_> = line.chars().filter_map(|c| c.to_digit(10)).collect();
-    *digits.first().unwrap() * 10 + *digits.last().unwrap()
+fn get_line_sum_part_one(line: &str) -> Option<u32> {
+    scan::digits_value(line)
 }
 
-fn get_line_sum_part_two(line: &str) -> u32 {
-    let mut matches = Vec::new();
-    for (number_str, val) in CONVERSION_TABLE {
-        for (idx, _) in line.match_indices(number_str) {
-            matches.push((idx, val));
-        }
-    }
-    matches.sort_by_key(|(idx, _)| *idx);
-    (matches.first().unwrap().1 * 10 + matches.last().unwrap().1) as u32
+fn get_line_sum_part_two(line: &str) -> Option<u32> {
+    scan::digits_and_words_value(line)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day01::puzzle_two;
 
-    use super::puzzle_one;
+    use super::{puzzle_one, CONVERSION_TABLE, DIGIT_WORDS};
+
+    #[test]
+    fn digit_words_follow_conversion_table() {
+        for (idx, word) in DIGIT_WORDS.iter().enumerate() {
+            assert!(CONVERSION_TABLE.contains(&(word, idx as u8 + 1)), "{word}");
+        }
+        assert_eq!(DIGIT_WORDS[0], "one");
+        assert_eq!(DIGIT_WORDS[8], "nine");
+    }
 
     #[test]
     fn example_puzzle_1() {
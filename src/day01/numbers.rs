@@ -0,0 +1,270 @@
+// Extended calibration values: besides single digits, a line may spell out
+// whole numbers below a thousand ("zero", "fifteen", "forty two",
+// "twenty-one", "nine hundred and ninety-nine") and, optionally, carry Roman
+// numerals ("XIV"). Words are lower case; numerals upper case.
+//
+// A token is the longest number starting at some byte. Tokens that lie
+// inside an earlier token are dropped ("one" in "twenty-one"), while
+// partially overlapping ones are kept, as in part two ("twenty-oneight"
+// yields 21 and 8). The calibration value concatenates the decimal digits of
+// the first and last token, which is `first * 10 + last` for single digits.
+
+use super::DIGIT_WORDS;
+
+const UNITS: [&str; 10] = units();
+const TEENS: [&str; 10] = [
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+// indexed from twenty
+const TENS: [&str; 8] = [
+    "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+// "zero" followed by the digit words of part two
+const fn units() -> [&'static str; 10] {
+    let mut units = ["zero"; 10];
+    let mut idx = 0;
+    while idx < DIGIT_WORDS.len() {
+        units[idx + 1] = DIGIT_WORDS[idx];
+        idx += 1;
+    }
+    units
+}
+
+const ROMAN: [(&str, u32); 13] = [
+    ("M", 1000),
+    ("CM", 900),
+    ("D", 500),
+    ("CD", 400),
+    ("C", 100),
+    ("XC", 90),
+    ("L", 50),
+    ("XL", 40),
+    ("X", 10),
+    ("IX", 9),
+    ("V", 5),
+    ("IV", 4),
+    ("I", 1),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct Token {
+    // byte offsets into the line, end exclusive
+    pub(super) start: usize,
+    pub(super) end: usize,
+    pub(super) value: u32,
+}
+
+pub(super) fn calibration_value(line: &str, roman: bool) -> Option<u32> {
+    let tokens = tokens(line, roman);
+    Some(combine(tokens.first()?.value, tokens.last()?.value))
+}
+
+fn combine(first: u32, last: u32) -> u32 {
+    let mut shift = 10;
+    while shift <= last {
+        shift *= 10;
+    }
+    first * shift + last
+}
+
+pub(super) fn tokens(line: &str, roman: bool) -> Vec<Token> {
+    let bytes = line.as_bytes();
+    let mut tokens: Vec<Token> = Vec::new();
+    for start in 0..bytes.len() {
+        let found = if bytes[start].is_ascii_digit() {
+            Some((start + 1, u32::from(bytes[start] - b'0')))
+        } else if roman && is_roman(bytes[start]) {
+            // only whole runs of numeral letters, so "XIV" is not also "IV"
+            let after_roman = start > 0 && is_roman(bytes[start - 1]);
+            (!after_roman)
+                .then(|| roman_numeral(bytes, start))
+                .flatten()
+        } else {
+            number(bytes, start)
+        };
+        let Some((end, value)) = found else {
+            continue;
+        };
+        if tokens.last().is_some_and(|prev| end <= prev.end) {
+            continue;
+        }
+        tokens.push(Token { start, end, value });
+    }
+    tokens
+}
+
+// hundreds are only spelled with a unit in front: "one hundred", not "hundred"
+fn number(bytes: &[u8], start: usize) -> Option<(usize, u32)> {
+    let (end, value) = below_hundred(bytes, start)?;
+    // teens and tens are never below ten, so this is a bare unit
+    if !(1..=9).contains(&value) {
+        return Some((end, value));
+    }
+    let Some(end) = next_word(bytes, end).find_map(|at| word(bytes, at, "hundred")) else {
+        return Some((end, value));
+    };
+
+    // "and" is optional: "one hundred and five" or "one hundred five"
+    let mut rest_starts: Vec<usize> = next_word(bytes, end).collect();
+    for at in rest_starts.clone() {
+        if let Some(after_and) = word(bytes, at, "and") {
+            rest_starts.extend(next_word(bytes, after_and));
+        }
+    }
+    let rest = rest_starts
+        .into_iter()
+        .filter_map(|at| below_hundred(bytes, at).filter(|(_, rest)| *rest > 0))
+        .max_by_key(|(end, _)| *end);
+    Some(match rest {
+        Some((rest_end, rest)) => (rest_end, value * 100 + rest),
+        None => (end, value * 100),
+    })
+}
+
+fn below_hundred(bytes: &[u8], start: usize) -> Option<(usize, u32)> {
+    if let Some((end, teen)) = longest(bytes, start, &TEENS) {
+        return Some((end, 10 + teen));
+    }
+    if let Some((end, tens)) = longest(bytes, start, &TENS) {
+        let tens = 20 + tens * 10;
+        let unit = next_word(bytes, end)
+            .find_map(|at| longest(bytes, at, &UNITS).filter(|(_, unit)| *unit > 0));
+        return Some(match unit {
+            Some((end, unit)) => (end, tens + unit),
+            None => (end, tens),
+        });
+    }
+    longest(bytes, start, &UNITS)
+}
+
+// where the next word of a compound may begin: straight away ("twentyone"),
+// or after a single '-' or ' '
+fn next_word(bytes: &[u8], at: usize) -> impl Iterator<Item = usize> {
+    let separated = matches!(bytes.get(at), Some(b'-' | b' '));
+    std::iter::once(at).chain(separated.then_some(at + 1))
+}
+
+fn word(bytes: &[u8], at: usize, word: &str) -> Option<usize> {
+    bytes
+        .get(at..)?
+        .starts_with(word.as_bytes())
+        .then_some(at + word.len())
+}
+
+// the end and table index of the longest table word at `at`
+fn longest(bytes: &[u8], at: usize, table: &[&str]) -> Option<(usize, u32)> {
+    table
+        .iter()
+        .zip(0..)
+        .filter_map(|(w, idx)| word(bytes, at, w).map(|end| (end, idx)))
+        .max_by_key(|(end, _)| *end)
+}
+
+fn is_roman(b: u8) -> bool {
+    matches!(b, b'I' | b'V' | b'X' | b'L' | b'C' | b'D' | b'M')
+}
+
+// the whole run of numeral letters at `start`, if it is written canonically
+// ("IV", not "IIII"), so values run from 1 to 3999
+fn roman_numeral(bytes: &[u8], start: usize) -> Option<(usize, u32)> {
+    let end = bytes[start..]
+        .iter()
+        .position(|b| !is_roman(*b))
+        .map_or(bytes.len(), |len| start + len);
+    let mut rest = &bytes[start..end];
+    let mut value = 0;
+    for (numeral, numeral_value) in ROMAN {
+        while let Some(stripped) = rest.strip_prefix(numeral.as_bytes()) {
+            rest = stripped;
+            value += numeral_value;
+        }
+    }
+    (value <= 3999 && to_roman(value).as_bytes() == &bytes[start..end]).then_some((end, value))
+}
+
+fn to_roman(mut value: u32) -> String {
+    let mut numeral = String::new();
+    for (letters, letters_value) in ROMAN {
+        while value >= letters_value {
+            numeral += letters;
+            value -= letters_value;
+        }
+    }
+    numeral
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(line: &str, roman: bool) -> Vec<u32> {
+        tokens(line, roman)
+            .iter()
+            .map(|token| token.value)
+            .collect()
+    }
+
+    #[test]
+    fn test_compound_words() {
+        assert_eq!(values("zero", false), vec![0]);
+        assert_eq!(values("sixteenx3", false), vec![16, 3]);
+        assert_eq!(values("twenty-one", false), vec![21]);
+        assert_eq!(values("forty two", false), vec![42]);
+        assert_eq!(values("ninetynine", false), vec![99]);
+        assert_eq!(values("twentyzero", false), vec![20, 0]);
+        assert_eq!(values("one hundred", false), vec![100]);
+        assert_eq!(values("nine hundred and ninety-nine", false), vec![999]);
+        assert_eq!(values("two hundred five", false), vec![205]);
+        assert_eq!(values("one hundred and", false), vec![100]);
+        assert_eq!(values("hundred", false), Vec::<u32>::new());
+        assert_eq!(values("one two", false), vec![1, 2]);
+        // partial overlaps are kept, as in part two
+        assert_eq!(values("eightwo", false), vec![8, 2]);
+        assert_eq!(values("twenty-oneight", false), vec![21, 8]);
+        assert_eq!(
+            tokens("ab fifty-five", false),
+            vec![Token {
+                start: 3,
+                end: 13,
+                value: 55
+            }]
+        );
+    }
+
+    #[test]
+    fn test_roman_numerals() {
+        assert_eq!(values("XIV", true), vec![14]);
+        assert_eq!(values("MCMXCIV", true), vec![1994]);
+        assert_eq!(values("xIIIx", true), vec![3]);
+        assert_eq!(values("IIII", true), Vec::<u32>::new());
+        assert_eq!(values("IC", true), Vec::<u32>::new());
+        assert_eq!(values("IVI", true), Vec::<u32>::new());
+        assert_eq!(values("MMMM", true), Vec::<u32>::new());
+        assert_eq!(values("XIV", false), Vec::<u32>::new());
+        assert_eq!(values("a1XLtwo", true), vec![1, 40, 2]);
+    }
+
+    #[test]
+    fn test_calibration_value() {
+        assert_eq!(combine(2, 3), 23);
+        assert_eq!(combine(21, 8), 218);
+        assert_eq!(combine(7, 100), 7100);
+        assert_eq!(combine(0, 0), 0);
+        assert_eq!(calibration_value("twenty-one and 4", false), Some(214));
+        assert_eq!(calibration_value("MMXXIII", true), Some(20232023));
+        assert_eq!(calibration_value("nothing", false), None);
+
+        // single digits and words combine as in part two
+        assert_eq!(calibration_value("two1nine", false), Some(29));
+        assert_eq!(calibration_value("xtwone3four", false), Some(24));
+    }
+}
@@ -0,0 +1,292 @@
+use std::{
+    fs,
+    io::{self, BufRead, BufReader},
+    sync::{mpsc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{
+    get_line_sum_part_one, get_line_sum_part_two,
+    stream::{calibrate, Calibration},
+};
+
+// how much is read before a chunk is handed to a worker; at most one chunk
+// per worker is queued, so memory stays around twice this per worker
+const CHUNK_BYTES: usize = 4 << 20;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(super) struct WorkerStats {
+    pub(super) chunks: usize,
+    pub(super) bytes: usize,
+    pub(super) lines: usize,
+    pub(super) rejected: usize,
+    pub(super) sum: u64,
+    // time spent summing, not waiting for chunks
+    pub(super) busy: Duration,
+}
+
+// `--parallel [--threads N] [--words] [path]`: reads the file at `path`, or
+// stdin, in chunks summed on N workers, at most one per available core
+pub(super) fn run(args: &[String]) {
+    let mut threads = available_threads();
+    let mut words = false;
+    let mut path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--words" => words = true,
+            "--threads" => match args.next().map(|val| val.parse()) {
+                Some(Ok(val)) => threads = val,
+                _ => {
+                    eprintln!("--threads expects a number of threads");
+                    return;
+                }
+            },
+            other if path.is_none() && !other.starts_with("--") => path = Some(other),
+            other => {
+                eprintln!("unknown parallel option: {other}");
+                return;
+            }
+        }
+    }
+
+    let line_sum = if words {
+        get_line_sum_part_two
+    } else {
+        get_line_sum_part_one
+    };
+    let instant = Instant::now();
+    let result = match path {
+        Some(path) => fs::File::open(path)
+            .and_then(|file| {
+                calibrate_parallel(BufReader::new(file), threads, CHUNK_BYTES, line_sum)
+            })
+            .map_err(|e| format!("could not read {path}: {e}")),
+        None => calibrate_parallel(io::stdin().lock(), threads, CHUNK_BYTES, line_sum)
+            .map_err(|e| format!("could not read stdin: {e}")),
+    };
+    let elapsed = instant.elapsed();
+    let (calibration, stats) = match result {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+
+    println!("sum: {}", calibration.sum);
+    println!(
+        "{} lines, {} rejected, {} ms",
+        calibration.lines,
+        calibration.rejected.len(),
+        elapsed.as_millis()
+    );
+    for (idx, worker) in stats.iter().enumerate() {
+        println!(
+            "  worker {idx}: {} chunks, {} bytes, {} lines, {} rejected, sum {}, {} ms busy",
+            worker.chunks,
+            worker.bytes,
+            worker.lines,
+            worker.rejected,
+            worker.sum,
+            worker.busy.as_millis()
+        );
+    }
+}
+
+fn available_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+// Same result as `calibrate` on the whole input, whatever the thread count
+// or chunk size: chunk results are merged in input order and rejected line
+// numbers are shifted by the lines of the chunks before them. Only the
+// per-worker split depends on scheduling. `threads` is capped at the
+// available parallelism.
+pub(super) fn calibrate_parallel<R: BufRead, F: Fn(&str) -> Option<u32> + Sync>(
+    reader: R,
+    threads: usize,
+    chunk_bytes: usize,
+    sum_first_and_last: F,
+) -> io::Result<(Calibration, Vec<WorkerStats>)> {
+    let threads = threads.clamp(1, available_threads());
+    calibrate_on_workers(reader, threads, chunk_bytes, sum_first_and_last)
+}
+
+// `calibrate_parallel` on exactly `threads` workers
+fn calibrate_on_workers<R: BufRead, F: Fn(&str) -> Option<u32> + Sync>(
+    mut reader: R,
+    threads: usize,
+    chunk_bytes: usize,
+    sum_first_and_last: F,
+) -> io::Result<(Calibration, Vec<WorkerStats>)> {
+    let (chunk_sender, chunk_receiver) = mpsc::sync_channel::<(usize, Vec<u8>)>(threads);
+    let chunk_receiver = Mutex::new(chunk_receiver);
+    let (result_sender, result_receiver) = mpsc::channel();
+
+    let stats = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                let result_sender = result_sender.clone();
+                let (chunk_receiver, sum_first_and_last) = (&chunk_receiver, &sum_first_and_last);
+                scope.spawn(move || {
+                    let mut stats = WorkerStats::default();
+                    loop {
+                        // a separate statement, so the lock is released
+                        // before the chunk is summed
+                        let next = chunk_receiver.lock().unwrap().recv();
+                        let Ok((idx, chunk)) = next else {
+                            break;
+                        };
+                        let instant = Instant::now();
+                        // reading from a slice cannot fail
+                        let calibration = calibrate(chunk.as_slice(), sum_first_and_last).unwrap();
+                        stats.busy += instant.elapsed();
+                        stats.chunks += 1;
+                        stats.bytes += chunk.len();
+                        stats.lines += calibration.lines;
+                        stats.rejected += calibration.rejected.len();
+                        stats.sum += calibration.sum;
+                        result_sender.send((idx, calibration)).unwrap();
+                    }
+                    stats
+                })
+            })
+            .collect();
+        drop(result_sender);
+
+        let mut read = Ok(());
+        for idx in 0.. {
+            match read_chunk(&mut reader, chunk_bytes) {
+                Ok(Some(chunk)) => {
+                    // only fails once every worker has gone, which join reports
+                    if chunk_sender.send((idx, chunk)).is_err() {
+                        break;
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    read = Err(e);
+                    break;
+                }
+            }
+        }
+        drop(chunk_sender);
+
+        let stats: Vec<WorkerStats> = workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .collect();
+        read.map(|()| stats)
+    })?;
+
+    let mut results: Vec<(usize, Calibration)> = result_receiver.into_iter().collect();
+    results.sort_by_key(|(idx, _)| *idx);
+    let mut total = Calibration::default();
+    for (_, calibration) in results {
+        let offset = total.lines;
+        total.sum += calibration.sum;
+        total.lines += calibration.lines;
+        total
+            .rejected
+            .extend(calibration.rejected.into_iter().map(|mut rejected| {
+                rejected.line += offset;
+                rejected
+            }));
+    }
+    Ok((total, stats))
+}
+
+// whole lines until at least `chunk_bytes` are read, so every chunk ends
+// just after a newline except the last, which keeps an unterminated final
+// line; a line longer than `chunk_bytes` makes a chunk of its own
+fn read_chunk<R: BufRead>(reader: &mut R, chunk_bytes: usize) -> io::Result<Option<Vec<u8>>> {
+    let mut chunk = Vec::with_capacity(chunk_bytes);
+    while chunk.len() < chunk_bytes.max(1) {
+        if reader.read_until(b'\n', &mut chunk)? == 0 {
+            break;
+        }
+    }
+    Ok((!chunk.is_empty()).then_some(chunk))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day01::DOCUMENT;
+
+    #[test]
+    fn test_read_chunks() {
+        let document = b"ab\ncd\r\nef\ngh";
+        for chunk_bytes in 0..=20 {
+            let mut reader = &document[..];
+            let mut chunks = Vec::new();
+            while let Some(chunk) = read_chunk(&mut reader, chunk_bytes).unwrap() {
+                chunks.push(chunk);
+            }
+
+            assert_eq!(chunks.concat(), document);
+            for chunk in &chunks[..chunks.len() - 1] {
+                assert!(chunk.ends_with(b"\n"));
+                // overshooting by less than the longest line
+                assert!(chunk.len() < chunk_bytes.max(1) + 4);
+            }
+        }
+        let mut reader = &document[..];
+        assert_eq!(read_chunk(&mut reader, 4).unwrap().unwrap(), b"ab\ncd\r\n");
+        assert_eq!(read_chunk(&mut reader, 4).unwrap().unwrap(), b"ef\ngh");
+        assert_eq!(read_chunk(&mut reader, 4).unwrap(), None);
+        assert_eq!(read_chunk(&mut &b""[..], 4).unwrap(), None);
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let mut document = DOCUMENT.replace("7", "").into_bytes();
+        // an unterminated last line with no digit at all
+        document.extend(b"\n\xffone\nabc");
+        let sequential = calibrate(document.as_slice(), get_line_sum_part_two).unwrap();
+        assert!(sequential.rejected.len() > 2);
+
+        for (threads, chunk_bytes) in [(1, CHUNK_BYTES), (2, 1), (3, 100), (8, 4096), (5000, 7)] {
+            let (calibration, stats) = calibrate_parallel(
+                document.as_slice(),
+                threads,
+                chunk_bytes,
+                get_line_sum_part_two,
+            )
+            .unwrap();
+
+            assert_eq!(calibration, sequential, "{threads} threads");
+            assert_eq!(
+                stats.iter().map(|worker| worker.bytes).sum::<usize>(),
+                document.len()
+            );
+            assert_eq!(
+                stats.iter().map(|worker| worker.sum).sum::<u64>(),
+                sequential.sum
+            );
+            assert!(stats.len() <= threads.min(available_threads()));
+        }
+    }
+
+    #[test]
+    fn test_workers_overlap() {
+        // sleeping needs no core, so the workers overlap wherever this runs
+        let slow_line_sum = |line: &str| {
+            thread::sleep(Duration::from_millis(25));
+            get_line_sum_part_one(line)
+        };
+        let document = "1\n2\n3\n4\n5\n6\n7\n8\n";
+        let instant = Instant::now();
+        let (calibration, stats) =
+            calibrate_on_workers(document.as_bytes(), 4, 1, slow_line_sum).unwrap();
+        let elapsed = instant.elapsed();
+
+        assert_eq!(calibration.sum, 11 + 22 + 33 + 44 + 55 + 66 + 77 + 88);
+        let busy: Duration = stats.iter().map(|worker| worker.busy).sum();
+        assert!(busy >= Duration::from_millis(200));
+        // one worker at a time would take at least as long as all the work
+        assert!(elapsed < busy * 3 / 4, "{elapsed:?} for {busy:?} of work");
+    }
+}
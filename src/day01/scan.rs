@@ -0,0 +1,93 @@
+// Only the first and last token of a line matter, so look for the first one
+// from the front and for the last one from the back, stopping at the first
+// hit each way. Works on bytes and never allocates.
+
+use super::{automaton::Match, DIGIT_WORDS};
+
+// the first and last digit of a line, as part one reads it
+pub(super) fn digits(line: &str) -> Option<(Match, Match)> {
+    let bytes = line.as_bytes();
+    let digit_at = |start: usize| Match {
+        start,
+        end: start + 1,
+        value: bytes[start] - b'0',
+    };
+    let first = bytes.iter().position(u8::is_ascii_digit)?;
+    let last = bytes.iter().rposition(u8::is_ascii_digit)?;
+    Some((digit_at(first), digit_at(last)))
+}
+
+// the first and last digit or spelled-out digit, as part two reads it
+pub(super) fn digits_and_words(line: &str) -> Option<(Match, Match)> {
+    let bytes = line.as_bytes();
+    let first = (0..bytes.len()).find_map(|start| token_at(bytes, start))?;
+    // the last token is the one with the highest start, so the backward
+    // search moves the start rather than the end
+    let last = (0..bytes.len())
+        .rev()
+        .find_map(|start| token_at(bytes, start))?;
+    Some((first, last))
+}
+
+pub(super) fn digits_value(line: &str) -> Option<u32> {
+    digits(line).map(calibration_value)
+}
+
+pub(super) fn digits_and_words_value(line: &str) -> Option<u32> {
+    digits_and_words(line).map(calibration_value)
+}
+
+fn calibration_value((first, last): (Match, Match)) -> u32 {
+    u32::from(first.value) * 10 + u32::from(last.value)
+}
+
+fn token_at(bytes: &[u8], start: usize) -> Option<Match> {
+    let rest = &bytes[start..];
+    // like `CONVERSION_TABLE`, which has no "0"
+    if let b'1'..=b'9' = rest[0] {
+        return Some(Match {
+            start,
+            end: start + 1,
+            value: rest[0] - b'0',
+        });
+    }
+    DIGIT_WORDS.iter().zip(1..).find_map(|(word, value)| {
+        rest.starts_with(word.as_bytes()).then_some(Match {
+            start,
+            end: start + word.len(),
+            value,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day01::{DOCUMENT, NUMBERS};
+
+    #[test]
+    fn test_scan_matches_automaton() {
+        for line in DOCUMENT
+            .lines()
+            .chain(["eightwo", "twone", "oneight", "7", "x", "", "héllo5wörld"])
+            .chain(["0one", "two0", "0", "10", "z0z"])
+        {
+            let collected: Vec<u32> = line.chars().filter_map(|c| c.to_digit(10)).collect();
+            let expected = collected.first().zip(collected.last());
+            assert_eq!(
+                digits_value(line),
+                expected.map(|(first, last)| first * 10 + last),
+                "{line}"
+            );
+            assert_eq!(
+                digits_and_words(line),
+                NUMBERS.first_and_last(line),
+                "{line}"
+            );
+        }
+        // part two never reads a 0
+        assert_eq!(digits_and_words_value("0one"), Some(11));
+        assert_eq!(digits_and_words_value("two0"), Some(22));
+        assert_eq!(digits_and_words_value("0"), None);
+    }
+}
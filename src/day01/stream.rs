@@ -0,0 +1,207 @@
+use std::{
+    fmt, fs,
+    io::{self, BufRead, BufReader},
+};
+
+use super::{
+    get_line_sum_part_one, numbers,
+    vocabulary::{Vocabulary, BUILTIN},
+};
+
+#[derive(Debug, PartialEq)]
+pub(super) enum LineError {
+    NoDigit,
+    InvalidUtf8,
+}
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LineError::NoDigit => write!(f, "no digit found"),
+            LineError::InvalidUtf8 => write!(f, "not valid UTF-8"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) struct Rejected {
+    // 1-based
+    pub(super) line: usize,
+    pub(super) text: String,
+    pub(super) error: LineError,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct Calibration {
+    pub(super) sum: u64,
+    pub(super) lines: usize,
+    pub(super) rejected: Vec<Rejected>,
+}
+
+// `--stream [--words] [--vocabulary <name|file>] [--ignore-case] [--extended]
+// [--roman] [path]`: reads the file at `path`, or stdin; words are English
+// unless a vocabulary is given, and `--extended` reads whole numbers.
+// `--ignore-case` only matters once words are read
+pub(super) fn run(args: &[String]) {
+    let mut extended = false;
+    let mut roman = false;
+    let mut words = false;
+    let mut ignore_case = false;
+    let mut vocabulary = None;
+    let mut path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--words" => words = true,
+            "--ignore-case" => ignore_case = true,
+            "--extended" => extended = true,
+            "--roman" => (extended, roman) = (true, true),
+            "--vocabulary" => {
+                let Some(spec) = args.next() else {
+                    eprintln!(
+                        "--vocabulary expects a file or one of {}",
+                        BUILTIN.join(", ")
+                    );
+                    return;
+                };
+                match Vocabulary::load(spec) {
+                    Ok(loaded) => vocabulary = Some(loaded),
+                    Err(e) => {
+                        eprintln!("{e}");
+                        return;
+                    }
+                }
+            }
+            other if path.is_none() && !other.starts_with("--") => path = Some(other),
+            other => {
+                eprintln!("unknown stream option: {other}");
+                return;
+            }
+        }
+    }
+    if extended && (vocabulary.is_some() || ignore_case) {
+        eprintln!("--extended only reads lower case English words");
+        return;
+    }
+    if vocabulary.is_none() && words {
+        vocabulary = Vocabulary::builtin("english");
+    }
+    let automaton = vocabulary.map(|vocabulary| vocabulary.automaton(ignore_case));
+    let line_sum = |line: &str| match &automaton {
+        _ if extended => numbers::calibration_value(line, roman),
+        Some(automaton) => automaton.calibration_value(line),
+        None => get_line_sum_part_one(line),
+    };
+
+    let result = match path {
+        Some(path) => fs::File::open(path)
+            .and_then(|file| calibrate(BufReader::new(file), line_sum))
+            .map_err(|e| format!("could not read {path}: {e}")),
+        None => calibrate(io::stdin().lock(), line_sum)
+            .map_err(|e| format!("could not read stdin: {e}")),
+    };
+    let calibration = match result {
+        Ok(calibration) => calibration,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+
+    println!("sum: {}", calibration.sum);
+    println!(
+        "{} lines, {} rejected",
+        calibration.lines,
+        calibration.rejected.len()
+    );
+    for rejected in &calibration.rejected {
+        println!(
+            "  line {}: {}: {:?}",
+            rejected.line, rejected.error, rejected.text
+        );
+    }
+}
+
+// one line in memory at a time, so documents of any size can be read
+pub(super) fn calibrate<R: BufRead, F: Fn(&str) -> Option<u32>>(
+    mut reader: R,
+    sum_first_and_last: F,
+) -> io::Result<Calibration> {
+    let mut calibration = Calibration::default();
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            break;
+        }
+        calibration.lines += 1;
+        let bytes = buf.strip_suffix(b"\n").unwrap_or(&buf);
+        let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+
+        let error = match std::str::from_utf8(bytes) {
+            Ok(line) => match sum_first_and_last(line) {
+                Some(value) => {
+                    calibration.sum += u64::from(value);
+                    continue;
+                }
+                None => LineError::NoDigit,
+            },
+            Err(_) => LineError::InvalidUtf8,
+        };
+        calibration.rejected.push(Rejected {
+            line: calibration.lines,
+            text: String::from_utf8_lossy(bytes).into_owned(),
+            error,
+        });
+    }
+    Ok(calibration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day01::{get_line_sum_part_two, puzzle_one, puzzle_two, DOCUMENT};
+
+    #[test]
+    fn test_calibrate_matches_puzzles() {
+        let one = calibrate(DOCUMENT.as_bytes(), get_line_sum_part_one).unwrap();
+        let two = calibrate(DOCUMENT.as_bytes(), get_line_sum_part_two).unwrap();
+
+        assert_eq!(one.sum, puzzle_one(DOCUMENT));
+        assert_eq!(two.sum, puzzle_two(DOCUMENT));
+        assert_eq!(one.lines, DOCUMENT.lines().count());
+        assert!(one.rejected.is_empty());
+    }
+
+    #[test]
+    fn test_calibrate_rejects_lines() {
+        let input: &[u8] = b"1abc2\r\nnodigits\npqr3stu8vwx\n\xff7\nseven";
+        let calibration = calibrate(input, get_line_sum_part_one).unwrap();
+
+        assert_eq!(calibration.sum, 12 + 38);
+        assert_eq!(calibration.lines, 5);
+        assert_eq!(
+            calibration.rejected,
+            vec![
+                Rejected {
+                    line: 2,
+                    text: "nodigits".to_string(),
+                    error: LineError::NoDigit,
+                },
+                Rejected {
+                    line: 4,
+                    text: "\u{fffd}7".to_string(),
+                    error: LineError::InvalidUtf8,
+                },
+                Rejected {
+                    line: 5,
+                    text: "seven".to_string(),
+                    error: LineError::NoDigit,
+                },
+            ]
+        );
+
+        let words = calibrate(input, get_line_sum_part_two).unwrap();
+        assert_eq!(words.sum, 12 + 38 + 77);
+        assert_eq!(words.rejected.len(), 2);
+    }
+}
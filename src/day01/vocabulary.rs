@@ -0,0 +1,209 @@
+use std::{fmt, fs};
+
+use super::{automaton::Automaton, CONVERSION_TABLE};
+
+const DIGITS: [(&str, u8); 9] = [
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
+];
+const NORWEGIAN: [&str; 9] = [
+    "en", "to", "tre", "fire", "fem", "seks", "sju", "åtte", "ni",
+];
+const FRENCH: [&str; 9] = [
+    "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf",
+];
+const GERMAN: [&str; 9] = [
+    "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+];
+const SPANISH: [&str; 9] = [
+    "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve",
+];
+
+pub(super) const BUILTIN: [&str; 5] = ["english", "norwegian", "french", "german", "spanish"];
+
+// the tokens a calibration line is scanned for, and the digit each stands for
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Vocabulary {
+    entries: Vec<(String, u8)>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) enum VocabularyError {
+    // not a "word value" pair
+    InvalidLine { line: usize, text: String },
+    InvalidValue { line: usize, token: String },
+    Empty,
+}
+impl fmt::Display for VocabularyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VocabularyError::InvalidLine { line, text } => {
+                write!(f, "line {line}: expected \"word value\", got {text:?}")
+            }
+            VocabularyError::InvalidValue { line, token } => {
+                write!(f, "line {line}: {token} is not a digit from 0 to 9")
+            }
+            VocabularyError::Empty => write!(f, "the vocabulary has no words"),
+        }
+    }
+}
+
+impl Vocabulary {
+    // digits are always part of a built-in vocabulary
+    pub(super) fn builtin(name: &str) -> Option<Vocabulary> {
+        let words = match name {
+            "english" => {
+                return Some(Vocabulary::from_table(&CONVERSION_TABLE));
+            }
+            "norwegian" => NORWEGIAN,
+            "french" => FRENCH,
+            "german" => GERMAN,
+            "spanish" => SPANISH,
+            _ => return None,
+        };
+        let mut vocabulary = Vocabulary::from_table(&DIGITS);
+        vocabulary.entries.extend(
+            (1..)
+                .zip(words)
+                .map(|(value, word)| (word.to_string(), value)),
+        );
+        Some(vocabulary)
+    }
+
+    // a built-in name, or else the path of a vocabulary file
+    pub(super) fn load(spec: &str) -> Result<Vocabulary, String> {
+        if let Some(vocabulary) = Vocabulary::builtin(spec) {
+            return Ok(vocabulary);
+        }
+        let text = fs::read_to_string(spec).map_err(|e| format!("could not read {spec}: {e}"))?;
+        Vocabulary::parse(&text).map_err(|e| format!("{spec}: {e}"))
+    }
+
+    fn from_table(table: &[(&str, u8)]) -> Vocabulary {
+        Vocabulary {
+            entries: table
+                .iter()
+                .map(|(word, value)| (word.to_string(), *value))
+                .collect(),
+        }
+    }
+
+    // one "word value" pair per line; blank lines and lines starting with
+    // '#' are skipped, and digits have to be listed like any other word
+    pub(super) fn parse(text: &str) -> Result<Vocabulary, VocabularyError> {
+        let mut entries = Vec::new();
+        for (idx, line) in text.lines().enumerate() {
+            let line_number = idx + 1;
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let mut tokens = trimmed.split_whitespace();
+            let (Some(word), Some(value), None) = (tokens.next(), tokens.next(), tokens.next())
+            else {
+                return Err(VocabularyError::InvalidLine {
+                    line: line_number,
+                    text: line.to_string(),
+                });
+            };
+            let value = value
+                .parse::<u8>()
+                .ok()
+                .filter(|value| *value <= 9)
+                .ok_or_else(|| VocabularyError::InvalidValue {
+                    line: line_number,
+                    token: value.to_string(),
+                })?;
+            entries.push((word.to_string(), value));
+        }
+        if entries.is_empty() {
+            return Err(VocabularyError::Empty);
+        }
+        Ok(Vocabulary { entries })
+    }
+
+    pub(super) fn automaton(&self, ignore_case: bool) -> Automaton {
+        let patterns: Vec<(&str, u8)> = self
+            .entries
+            .iter()
+            .map(|(word, value)| (word.as_str(), *value))
+            .collect();
+        Automaton::new(&patterns, ignore_case)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(vocabulary: &Vocabulary, ignore_case: bool, line: &str) -> Option<u32> {
+        vocabulary.automaton(ignore_case).calibration_value(line)
+    }
+
+    #[test]
+    fn test_builtin_vocabularies() {
+        let norwegian = Vocabulary::builtin("norwegian").unwrap();
+        assert_eq!(value(&norwegian, false, "xåttefemtox"), Some(82));
+        assert_eq!(value(&norwegian, false, "seks"), Some(66));
+
+        let french = Vocabulary::builtin("french").unwrap();
+        assert_eq!(value(&french, false, "deuxtroisneuf"), Some(29));
+
+        let german = Vocabulary::builtin("german").unwrap();
+        assert_eq!(value(&german, false, "fünfundzwanzig3"), Some(53));
+
+        let spanish = Vocabulary::builtin("spanish").unwrap();
+        assert_eq!(value(&spanish, false, "unosiete"), Some(17));
+
+        for name in BUILTIN {
+            assert!(Vocabulary::builtin(name).is_some(), "{name}");
+        }
+        assert_eq!(Vocabulary::builtin("klingon"), None);
+    }
+
+    #[test]
+    fn test_ignore_case() {
+        let german = Vocabulary::builtin("german").unwrap();
+        assert_eq!(value(&german, false, "FÜNF und Acht"), None);
+        assert_eq!(value(&german, true, "FÜNF und Acht"), Some(58));
+        assert_eq!(value(&german, true, "fÜnf"), Some(55));
+
+        let norwegian = Vocabulary::builtin("norwegian").unwrap();
+        assert_eq!(value(&norwegian, true, "ÅTTE"), Some(88));
+    }
+
+    #[test]
+    fn test_parse_vocabulary() {
+        let vocabulary = Vocabulary::parse("# binary\n\nzero 0\n  one 1 \n").unwrap();
+        assert_eq!(value(&vocabulary, false, "onezeroone"), Some(11));
+        assert_eq!(value(&vocabulary, false, "zero"), Some(0));
+        // digits are not implied for loaded vocabularies
+        assert_eq!(value(&vocabulary, false, "1"), None);
+
+        assert_eq!(
+            Vocabulary::parse("one 1\ntwo\n"),
+            Err(VocabularyError::InvalidLine {
+                line: 2,
+                text: "two".to_string()
+            })
+        );
+        assert_eq!(
+            Vocabulary::parse("ten 10\n"),
+            Err(VocabularyError::InvalidValue {
+                line: 1,
+                token: "10".to_string()
+            })
+        );
+        assert_eq!(
+            Vocabulary::parse("# nothing\n"),
+            Err(VocabularyError::Empty)
+        );
+    }
+}
@@ -0,0 +1,429 @@
+use std::{f64::consts::PI, fmt, ops::RangeInclusive};
+
+use super::{bag::Bag, parse_games, CubeDraw, Game, Games, Palette, INPUT};
+
+// Each draw takes a handful of cubes out of the bag at once and puts them
+// back before the next one, so draws are independent and a single draw
+// follows the multivariate hypergeometric distribution: with N_i cubes of
+// colour i out of T, showing c_i of each (n in all) has probability
+// prod C(N_i, c_i) / C(T, n). Everything is kept as a natural log, as the
+// probability of a whole record underflows an f64.
+
+// a bag's cube counts indexed by colour, plus the cubes of colours outside
+// the palette, which no game can have shown
+#[derive(Debug, Clone, PartialEq)]
+struct Composition {
+    counts: Vec<u32>,
+    unseen: u64,
+}
+impl Composition {
+    fn from_bag(bag: &Bag, palette: &Palette) -> Composition {
+        let mut counts = vec![0; palette.len()];
+        let mut unseen = 0;
+        for (name, count) in &bag.cubes {
+            match palette.get(name) {
+                Some(color) => counts[color.index()] = *count,
+                // a `Bag` has too few entries for this to overflow
+                None => unseen += u64::from(*count),
+            }
+        }
+        Composition { counts, unseen }
+    }
+
+    // `None` when the cubes do not fit in a u64
+    fn total(&self) -> Option<u64> {
+        self.counts.iter().try_fold(self.unseen, |total, count| {
+            total.checked_add(u64::from(*count))
+        })
+    }
+
+    fn to_bag(&self, palette: &Palette) -> Bag {
+        let cubes = palette
+            .colors()
+            .zip(&self.counts)
+            .map(|(color, count)| (palette.name(color).to_string(), *count))
+            .collect();
+        Bag { cubes }
+    }
+}
+
+// ln(k!), looked up for small k and from Stirling's series beyond, so a
+// bag of any size costs the same
+struct LnFactorials([f64; LnFactorials::EXACT]);
+impl LnFactorials {
+    // the series is off by less than 1e-15 from here on
+    const EXACT: usize = 256;
+
+    fn new() -> LnFactorials {
+        let mut table = [0.0; LnFactorials::EXACT];
+        for k in 1..table.len() {
+            table[k] = table[k - 1] + (k as f64).ln();
+        }
+        LnFactorials(table)
+    }
+
+    fn ln_factorial(&self, k: u64) -> f64 {
+        if let Some(ln) = usize::try_from(k).ok().and_then(|k| self.0.get(k)) {
+            return *ln;
+        }
+        let k = k as f64;
+        k * k.ln() - k + 0.5 * (2.0 * PI * k).ln() + 1.0 / (12.0 * k) - 1.0 / (360.0 * k.powi(3))
+    }
+
+    // ln C(n, k), -inf when k > n
+    fn ln_choose(&self, n: u64, k: u64) -> f64 {
+        if k > n {
+            return f64::NEG_INFINITY;
+        }
+        self.ln_factorial(n) - self.ln_factorial(k) - self.ln_factorial(n - k)
+    }
+}
+
+fn draw_ln_probability(draw: &CubeDraw, bag: &Composition, total: u64, ln: &LnFactorials) -> f64 {
+    // no more than `total`, as each count is checked against the bag
+    let mut drawn = 0;
+    let mut ln_p = 0.0;
+    for (color, count) in &draw.colors {
+        let available = bag.counts[color.index()];
+        if *count > available {
+            // also keeps -inf - -inf from turning into NaN below
+            return f64::NEG_INFINITY;
+        }
+        ln_p += ln.ln_choose(u64::from(available), u64::from(*count));
+        drawn += u64::from(*count);
+    }
+    ln_p - ln.ln_choose(total, drawn)
+}
+
+fn game_ln_probability(game: &Game, bag: &Composition, total: u64, ln: &LnFactorials) -> f64 {
+    game.cube_draws
+        .iter()
+        .map(|draw| draw_ln_probability(draw, bag, total, ln))
+        .sum()
+}
+
+// the natural log of the probability of every game, in order; `None` when
+// the bag holds more cubes than a u64 counts
+fn game_likelihoods(games: &Games, bag: &Bag) -> Option<Vec<(u32, f64)>> {
+    let bag = Composition::from_bag(bag, &games.palette);
+    let total = bag.total()?;
+    let ln = LnFactorials::new();
+    let likelihoods = games
+        .games
+        .iter()
+        .map(|game| (game.id, game_ln_probability(game, &bag, total, &ln)))
+        .collect();
+    Some(likelihoods)
+}
+
+#[derive(Debug, PartialEq)]
+struct Estimate {
+    bag: Bag,
+    // of all the games together
+    ln_likelihood: f64,
+}
+
+// the most bags `most_likely_bag` tries, a few seconds' work
+const MAX_BAGS: u64 = 1 << 20;
+
+#[derive(Debug, PartialEq)]
+enum SearchError {
+    // there must be one range per palette colour
+    Bounds { colours: usize, bounds: usize },
+    // a draw shows more cubes of some colour than its upper bound
+    NoBagFits,
+    // more than `MAX_BAGS` bags within the bounds
+    TooManyBags,
+}
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchError::Bounds { colours, bounds } => {
+                write!(f, "{bounds} bounds for {colours} colours")
+            }
+            SearchError::NoBagFits => write!(f, "no bag within the bounds fits every game"),
+            SearchError::TooManyBags => write!(f, "more than {MAX_BAGS} bags to search"),
+        }
+    }
+}
+
+// The bag, with each colour's count inside its bounds (indexed by colour),
+// under which the record is most probable. Counts below the most cubes of a
+// colour any draw shows make the record impossible, so the search starts
+// there and tries every bag from there up to the bounds. Bigger bags often
+// keep getting likelier as draws come closer to being with replacement, so
+// the upper bounds matter. Ties go to the bag reached first, which has the
+// fewest cubes of the last colours.
+fn most_likely_bag(games: &Games, bounds: &[RangeInclusive<u32>]) -> Result<Estimate, SearchError> {
+    if bounds.len() != games.palette.len() {
+        return Err(SearchError::Bounds {
+            colours: games.palette.len(),
+            bounds: bounds.len(),
+        });
+    }
+    let mut lows: Vec<u32> = bounds.iter().map(|range| *range.start()).collect();
+    for game in &games.games {
+        for draw in &game.cube_draws {
+            for (color, count) in &draw.colors {
+                let low = &mut lows[color.index()];
+                *low = (*low).max(*count);
+            }
+        }
+    }
+    if lows
+        .iter()
+        .zip(bounds)
+        .any(|(low, range)| low > range.end())
+    {
+        return Err(SearchError::NoBagFits);
+    }
+    let bags = lows
+        .iter()
+        .zip(bounds)
+        .try_fold(1u64, |bags, (low, range)| {
+            bags.checked_mul(u64::from(range.end() - low) + 1)
+        });
+    if bags.is_none_or(|bags| bags > MAX_BAGS) {
+        return Err(SearchError::TooManyBags);
+    }
+
+    let ln = LnFactorials::new();
+    let mut bag = Composition {
+        counts: lows.clone(),
+        unseen: 0,
+    };
+    let mut best: Option<(Vec<u32>, f64)> = None;
+    loop {
+        let total = bag
+            .total()
+            .expect("a palette's worth of u32 counts fits in a u64");
+        let ln_likelihood = games
+            .games
+            .iter()
+            .map(|game| game_ln_probability(game, &bag, total, &ln))
+            .sum();
+        if best.as_ref().is_none_or(|(_, best)| ln_likelihood > *best) {
+            best = Some((bag.counts.clone(), ln_likelihood));
+        }
+
+        // odometer step, the first colour turning fastest
+        let mut idx = 0;
+        loop {
+            if idx == bag.counts.len() {
+                let (counts, ln_likelihood) = best.expect("at least one bag was tried");
+                let bag = Composition { counts, unseen: 0 }.to_bag(&games.palette);
+                return Ok(Estimate { bag, ln_likelihood });
+            }
+            if bag.counts[idx] < *bounds[idx].end() {
+                bag.counts[idx] += 1;
+                break;
+            }
+            bag.counts[idx] = lows[idx];
+            idx += 1;
+        }
+    }
+}
+
+// `--likelihood [--bag <colour=count,...>]` or `--most-likely [--max N]`
+pub(super) fn run(args: &[String]) {
+    let games = match parse_games(INPUT, Palette::standard()) {
+        Ok(games) => games,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    match (args.first().map(String::as_str), args.get(1), args.get(2)) {
+        (Some("--likelihood"), None, _) => print_likelihoods(&games, &Bag::standard()),
+        (Some("--likelihood"), Some(option), Some(spec)) if option == "--bag" => {
+            match Bag::parse(spec) {
+                Ok(bag) => print_likelihoods(&games, &bag),
+                Err(e) => eprintln!("invalid bag: {e}"),
+            }
+        }
+        (Some("--most-likely"), None, _) => print_most_likely(&games, 20),
+        (Some("--most-likely"), Some(option), Some(max)) if option == "--max" => {
+            match max.parse() {
+                Ok(max) => print_most_likely(&games, max),
+                Err(_) => eprintln!("--max expects a cube count, got {max:?}"),
+            }
+        }
+        _ => {
+            eprintln!("expected --likelihood [--bag <colour=count,...>] or --most-likely [--max N]")
+        }
+    }
+}
+
+fn print_likelihoods(games: &Games, bag: &Bag) {
+    let Some(likelihoods) = game_likelihoods(games, bag) else {
+        eprintln!("the bag holds more than {} cubes", u64::MAX);
+        return;
+    };
+    println!("bag: {bag}");
+    for (id, ln_p) in &likelihoods {
+        println!("  game {id}: p = {:.3e} (ln {ln_p:.3})", ln_p.exp());
+    }
+    let total: f64 = likelihoods.iter().map(|(_, ln_p)| ln_p).sum();
+    println!("ln likelihood of all games: {total:.3}");
+}
+
+fn print_most_likely(games: &Games, max: u32) {
+    let bounds = vec![0..=max; games.palette.len()];
+    match most_likely_bag(games, &bounds) {
+        Ok(estimate) => {
+            println!("most likely bag: {}", estimate.bag);
+            println!("ln likelihood of all games: {:.3}", estimate.ln_likelihood);
+        }
+        Err(SearchError::NoBagFits) => {
+            println!("no bag with at most {max} cubes of each colour fits every game")
+        }
+        Err(e) => eprintln!("--max {max}: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day02::tests::EXAMPLE_INPUT;
+
+    fn games(input: &str) -> Games {
+        parse_games(input, Palette::standard()).unwrap()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn game_probabilities() {
+        let games = games(
+            "Game 1: 1 red
+Game 2: 1 red; 1 red, 1 blue
+Game 3: 2 blue",
+        );
+        let bag = Bag::parse("red=2,blue=1").unwrap();
+        let likelihoods = game_likelihoods(&games, &bag).unwrap();
+
+        assert_eq!(likelihoods[0].0, 1);
+        // 2 of the 3 cubes are red
+        assert_close(likelihoods[0].1.exp(), 2.0 / 3.0);
+        // then C(2, 1) * C(1, 1) / C(3, 2) for the second draw
+        assert_close(likelihoods[1].1.exp(), 4.0 / 9.0);
+        // only one blue cube, and fewer cubes in the bag than drawn
+        assert_eq!(likelihoods[2].1, f64::NEG_INFINITY);
+        let bag = Bag::parse("red=1").unwrap();
+        assert_eq!(
+            game_likelihoods(&games, &bag).unwrap()[2].1,
+            f64::NEG_INFINITY
+        );
+    }
+
+    #[test]
+    fn colours_outside_the_palette_dilute_draws() {
+        let games = games("Game 1: 1 red");
+        let bag = Bag::parse("red=1,purple=3").unwrap();
+
+        assert_close(game_likelihoods(&games, &bag).unwrap()[0].1.exp(), 0.25);
+    }
+
+    #[test]
+    fn most_likely_bag_within_bounds() {
+        let games = games("Game 1: 2 red, 1 blue; 1 red");
+        let bounds = vec![0..=5; 3];
+        let estimate = most_likely_bag(&games, &bounds).unwrap();
+
+        // green never shows up, so the best bag holds none
+        assert_eq!(estimate.bag, Bag::parse("red=2,green=0,blue=1").unwrap());
+        // C(2, 2) * C(1, 1) / C(3, 3) then C(2, 1) / C(3, 1)
+        assert_close(estimate.ln_likelihood, (2.0f64 / 3.0).ln());
+
+        // a lower bound is respected even when it costs likelihood
+        let bounds = vec![0..=5, 1..=5, 0..=5];
+        let estimate = most_likely_bag(&games, &bounds).unwrap();
+        assert_eq!(estimate.bag.cubes["green"], 1);
+
+        let bounds = vec![0..=1; 3];
+        assert_eq!(
+            most_likely_bag(&games, &bounds),
+            Err(SearchError::NoBagFits)
+        );
+
+        assert_eq!(
+            most_likely_bag(&games, &[0..=5, 0..=5]),
+            Err(SearchError::Bounds {
+                colours: 3,
+                bounds: 2
+            })
+        );
+        // 2^10 * 2^10 bags is the most searched
+        let bounds = vec![2..=1025, 0..=1023, 1..=1];
+        assert!(most_likely_bag(&games, &bounds).is_ok());
+        let bounds = vec![2..=1025, 0..=1024, 1..=1];
+        assert_eq!(
+            most_likely_bag(&games, &bounds),
+            Err(SearchError::TooManyBags)
+        );
+        let bounds = vec![0..=u32::MAX; 3];
+        assert_eq!(
+            most_likely_bag(&games, &bounds),
+            Err(SearchError::TooManyBags)
+        );
+    }
+
+    #[test]
+    fn ln_choose_for_big_counts() {
+        let ln = LnFactorials::new();
+        let summed = |n: u64, k: u64| -> f64 {
+            (0..k).map(|i| ((n - i) as f64 / (i + 1) as f64).ln()).sum()
+        };
+
+        assert_close(ln.ln_choose(10, 3), 120f64.ln());
+        for (n, k) in [(300, 2), (300, 150), (1000, 999), (100_000, 40)] {
+            let expected = summed(n, k);
+            let actual = ln.ln_choose(n, k);
+            assert!(
+                (actual - expected).abs() < 1e-9 * expected.max(1.0),
+                "C({n}, {k})"
+            );
+        }
+        assert_eq!(ln.ln_choose(3, 4), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn likelihoods_of_huge_bags() {
+        let games = games("Game 1: 1 red, 1 blue");
+        // more cubes than a u32 counts, and no table of their factorials
+        let bag = Bag::parse(&format!("red={0},blue={0}", u32::MAX)).unwrap();
+        let likelihoods = game_likelihoods(&games, &bag).unwrap();
+
+        // very nearly one red then one blue with replacement, either way round
+        assert!((likelihoods[0].1.exp() - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn most_likely_bag_for_example() {
+        let games = games(EXAMPLE_INPUT);
+        let estimate = most_likely_bag(&games, &vec![0..=20; 3]).unwrap();
+
+        // the estimate must explain every game and beat the puzzle's bag
+        let bag = estimate.bag.resolve(&games.palette);
+        assert!(games
+            .games
+            .iter()
+            .all(|game| bag.first_violation(game).is_none()));
+        let standard: f64 = game_likelihoods(&games, &Bag::standard())
+            .unwrap()
+            .iter()
+            .map(|(_, ln_p)| ln_p)
+            .sum();
+        assert!(estimate.ln_likelihood >= standard);
+        assert_close(
+            game_likelihoods(&games, &estimate.bag)
+                .unwrap()
+                .iter()
+                .map(|(_, ln_p)| ln_p)
+                .sum(),
+            estimate.ln_likelihood,
+        );
+    }
+}
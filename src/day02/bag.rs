@@ -0,0 +1,278 @@
+use std::{collections::BTreeMap, fmt, fs};
+
+use super::{parse_games, Game, Palette, INPUT};
+
+// the cubes a game is checked against; colours missing from the bag have
+// no cubes at all
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Bag {
+    pub(super) cubes: BTreeMap<String, u32>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) enum BagError {
+    // not "colour=count"
+    InvalidEntry(String),
+    InvalidCount { color: String, count: String },
+    DuplicateColor(String),
+    Empty,
+}
+impl fmt::Display for BagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BagError::InvalidEntry(entry) => {
+                write!(f, "expected colour=count, got {entry:?}")
+            }
+            BagError::InvalidCount { color, count } => {
+                write!(f, "{count:?} is not a valid count for {color}")
+            }
+            BagError::DuplicateColor(color) => write!(f, "{color} is listed twice"),
+            BagError::Empty => write!(f, "the bag has no colours"),
+        }
+    }
+}
+
+// the first cube count in a game that the bag cannot satisfy
+#[derive(Debug, PartialEq)]
+pub(super) struct Violation {
+    pub(super) game: u32,
+    // 1-based, in the order written
+    pub(super) draw: usize,
+    pub(super) color: String,
+    pub(super) count: u32,
+    pub(super) available: u32,
+}
+
+impl Bag {
+    // the bag of the puzzle
+    pub(super) fn standard() -> Bag {
+        Bag {
+            cubes: [("red", 12), ("green", 13), ("blue", 14)]
+                .map(|(color, count)| (color.to_string(), count))
+                .into(),
+        }
+    }
+
+    // "red=12,green=13,blue=14"; entries may also be separated by
+    // whitespace or newlines, so a file can hold one per line
+    pub(super) fn parse(spec: &str) -> Result<Bag, BagError> {
+        let mut cubes = BTreeMap::new();
+        for entry in spec
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|entry| !entry.is_empty())
+        {
+            let (color, count) = entry
+                .split_once('=')
+                .filter(|(color, _)| !color.is_empty())
+                .ok_or_else(|| BagError::InvalidEntry(entry.to_string()))?;
+            let count = count.parse().map_err(|_| BagError::InvalidCount {
+                color: color.to_string(),
+                count: count.to_string(),
+            })?;
+            if cubes.insert(color.to_string(), count).is_some() {
+                return Err(BagError::DuplicateColor(color.to_string()));
+            }
+        }
+        if cubes.is_empty() {
+            return Err(BagError::Empty);
+        }
+        Ok(Bag { cubes })
+    }
+
+    // for a palette that is complete, i.e. once every game is parsed
+    pub(super) fn resolve<'a>(&self, palette: &'a Palette) -> ResolvedBag<'a> {
+        let counts = palette
+            .colors()
+            .map(|color| self.cubes.get(palette.name(color)).copied().unwrap_or(0))
+            .collect();
+        ResolvedBag { counts, palette }
+    }
+}
+impl fmt::Display for Bag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries: Vec<String> = self
+            .cubes
+            .iter()
+            .map(|(color, count)| format!("{color}={count}"))
+            .collect();
+        write!(f, "{}", entries.join(","))
+    }
+}
+
+// a bag's counts indexed by colour, so checking a cube needs no lookup by
+// name
+pub(super) struct ResolvedBag<'a> {
+    counts: Vec<u32>,
+    palette: &'a Palette,
+}
+impl ResolvedBag<'_> {
+    pub(super) fn first_violation(&self, game: &Game) -> Option<Violation> {
+        for (idx, draw) in game.cube_draws.iter().enumerate() {
+            for (color, count) in &draw.colors {
+                let available = self.counts[color.index()];
+                if *count > available {
+                    return Some(Violation {
+                        game: game.id,
+                        draw: idx + 1,
+                        color: self.palette.name(*color).to_string(),
+                        count: *count,
+                        available,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+// `--bag <colour=count,...>` or `--bag-file <path>`
+pub(super) fn run(args: &[String]) {
+    let (Some(option), Some(value)) = (args.first(), args.get(1)) else {
+        eprintln!("expected --bag <colour=count,...> or --bag-file <path>");
+        return;
+    };
+    let spec = match option.as_str() {
+        "--bag" => value.clone(),
+        "--bag-file" => match fs::read_to_string(value) {
+            Ok(spec) => spec,
+            Err(e) => {
+                eprintln!("could not read {value}: {e}");
+                return;
+            }
+        },
+        other => {
+            eprintln!("unknown bag option: {other}");
+            return;
+        }
+    };
+    let bag = match Bag::parse(&spec) {
+        Ok(bag) => bag,
+        Err(e) => {
+            eprintln!("invalid bag: {e}");
+            return;
+        }
+    };
+    let games = match parse_games(INPUT, Palette::standard()) {
+        Ok(games) => games,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+
+    let resolved = bag.resolve(&games.palette);
+    let mut sum = 0;
+    let mut violations: Vec<Violation> = Vec::new();
+    for game in &games.games {
+        match resolved.first_violation(game) {
+            Some(violation) => violations.push(violation),
+            None => sum += game.id,
+        }
+    }
+    println!("bag: {bag}");
+    println!("valid game id sum: {sum}");
+    println!("{} invalid games:", violations.len());
+    for violation in &violations {
+        println!(
+            "  game {}: draw {} shows {} {}, the bag holds {}",
+            violation.game, violation.draw, violation.count, violation.color, violation.available
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day02::{parse_line, tests::EXAMPLE_INPUT};
+
+    #[test]
+    fn test_parse_bag() {
+        assert_eq!(
+            Bag::parse("red=12, green=13,blue=14").unwrap(),
+            Bag::standard()
+        );
+        assert_eq!(
+            Bag::parse("red=12\ngreen=13\n\nblue=14\n").unwrap(),
+            Bag::standard()
+        );
+        assert_eq!(Bag::standard().to_string(), "blue=14,green=13,red=12");
+
+        assert_eq!(
+            Bag::parse("red:12"),
+            Err(BagError::InvalidEntry("red:12".to_string()))
+        );
+        assert_eq!(
+            Bag::parse("=12"),
+            Err(BagError::InvalidEntry("=12".to_string()))
+        );
+        assert_eq!(
+            Bag::parse("red=lots"),
+            Err(BagError::InvalidCount {
+                color: "red".to_string(),
+                count: "lots".to_string()
+            })
+        );
+        assert_eq!(
+            Bag::parse("red=1,red=2"),
+            Err(BagError::DuplicateColor("red".to_string()))
+        );
+        assert_eq!(Bag::parse(" , "), Err(BagError::Empty));
+    }
+
+    #[test]
+    fn test_first_violation() {
+        let games = parse_games(EXAMPLE_INPUT, Palette::standard()).unwrap();
+        let violations: Vec<Violation> = games
+            .games
+            .iter()
+            .filter_map(|game| {
+                Bag::standard()
+                    .resolve(&games.palette)
+                    .first_violation(game)
+            })
+            .collect();
+
+        assert_eq!(
+            violations,
+            vec![
+                Violation {
+                    game: 3,
+                    draw: 1,
+                    color: "red".to_string(),
+                    count: 20,
+                    available: 12,
+                },
+                Violation {
+                    game: 4,
+                    draw: 3,
+                    color: "blue".to_string(),
+                    count: 15,
+                    available: 14,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_arbitrary_colours() {
+        let mut palette = Palette::standard();
+        let game = parse_line(1, "Game 7: 2 purple, 1 red; 3 purple", &mut palette).unwrap();
+
+        assert_eq!(
+            Bag::standard()
+                .resolve(&palette)
+                .first_violation(&game)
+                .map(|v| v.available),
+            Some(0)
+        );
+        let bag = Bag::parse("purple=2,red=1").unwrap();
+        assert_eq!(
+            bag.resolve(&palette)
+                .first_violation(&game)
+                .map(|v| (v.draw, v.count)),
+            Some((2, 3))
+        );
+        let bag = Bag::parse("purple=3,red=1").unwrap();
+        assert_eq!(bag.resolve(&palette).first_violation(&game), None);
+    }
+}
@@ -0,0 +1,65 @@
+use crate::bench::{report, time};
+
+use super::{color::OwnedNames, parse_games, parse_lines, Palette, INPUT};
+
+const DEFAULT_COPIES: usize = 200;
+
+// `--bench [copies]`: parses the puzzle input repeated `copies` times
+pub(super) fn run(args: &[String]) {
+    let copies = match args.first().map(|arg| (arg, arg.parse())) {
+        None => DEFAULT_COPIES,
+        Some((_, Ok(copies))) => copies,
+        Some((arg, Err(e))) => {
+            eprintln!("could not parse {arg}: {e}");
+            return;
+        }
+    };
+    let input = vec![INPUT; copies].join("\n");
+    let lines = input.lines().count() as u64;
+    println!("{lines} games, {} bytes", input.len());
+
+    // the same parser keeping a `String` per cube, as it did before interning
+    let (keyed, keyed_time) = time(|| parse_lines(&input, &mut OwnedNames));
+    let (typed, typed_time) = time(|| parse_games(&input, Palette::standard()));
+    let (keyed, typed) = match (keyed, typed) {
+        (Ok(keyed), Ok(typed)) => (keyed, typed),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    assert_eq!(keyed.len(), typed.games.len());
+    report("parse, string keyed draws", lines, "games", keyed_time);
+    report("parse, interned colours", lines, "games", typed_time);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day02::tests::EXAMPLE_INPUT;
+
+    #[test]
+    fn test_storages_agree() {
+        let keyed = parse_lines(EXAMPLE_INPUT, &mut OwnedNames).unwrap();
+        let typed = parse_games(EXAMPLE_INPUT, Palette::standard()).unwrap();
+
+        assert_eq!(keyed.len(), typed.games.len());
+        for (keyed, game) in keyed.iter().zip(&typed.games) {
+            assert_eq!(keyed.id, game.id);
+            assert_eq!(keyed.cube_draws.len(), game.cube_draws.len());
+            for (keyed, draw) in keyed.cube_draws.iter().zip(&game.cube_draws) {
+                let typed: Vec<(&str, u32)> = draw
+                    .colors
+                    .iter()
+                    .map(|(color, count)| (typed.palette.name(*color), *count))
+                    .collect();
+                let keyed: Vec<(&str, u32)> = keyed
+                    .colors
+                    .iter()
+                    .map(|(color, count)| (color.as_str(), *count))
+                    .collect();
+                assert_eq!(keyed, typed);
+            }
+        }
+    }
+}
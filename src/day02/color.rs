@@ -0,0 +1,186 @@
+use std::fmt;
+
+// Cube colours are interned per palette: the known colours come first, and
+// in lenient mode any other name met while parsing is appended after them.
+// A strict palette rejects names it does not know.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(super) struct Color(u16);
+impl Color {
+    pub(super) fn index(self) -> usize {
+        usize::from(self.0)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) enum PaletteError {
+    // only raised for a strict palette
+    UnknownColor,
+    // every `Color` is taken
+    TooManyColors,
+}
+impl fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaletteError::UnknownColor => write!(f, "unknown colour"),
+            PaletteError::TooManyColors => {
+                write!(f, "more than {} colours", usize::from(u16::MAX) + 1)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Palette {
+    names: Vec<String>,
+    // how many of `names` were declared up front
+    known: usize,
+    strict: bool,
+}
+impl Palette {
+    pub(super) fn new<'a>(
+        names: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Palette, PaletteError> {
+        let mut palette = Palette {
+            names: Vec::new(),
+            known: 0,
+            strict: false,
+        };
+        for name in names {
+            palette.intern(name)?;
+        }
+        palette.known = palette.names.len();
+        Ok(palette)
+    }
+
+    pub(super) fn standard() -> Palette {
+        Palette::new(["red", "green", "blue"]).expect("three colours fit")
+    }
+
+    pub(super) fn strict(self) -> Palette {
+        Palette {
+            strict: true,
+            ..self
+        }
+    }
+
+    pub(super) fn resolve(&mut self, name: &str) -> Result<Color, PaletteError> {
+        match self.get(name) {
+            Some(color) => Ok(color),
+            None if self.strict => Err(PaletteError::UnknownColor),
+            None => self.intern(name),
+        }
+    }
+
+    fn intern(&mut self, name: &str) -> Result<Color, PaletteError> {
+        if let Some(color) = self.get(name) {
+            return Ok(color);
+        }
+        let color = u16::try_from(self.names.len()).map_err(|_| PaletteError::TooManyColors)?;
+        self.names.push(name.to_string());
+        Ok(Color(color))
+    }
+
+    pub(super) fn get(&self, name: &str) -> Option<Color> {
+        self.colors().find(|color| self.name(*color) == name)
+    }
+
+    // every colour, declared ones first
+    pub(super) fn colors(&self) -> impl Iterator<Item = Color> {
+        // `intern` keeps the number of names within what `Color` can count
+        (0..=u16::MAX).take(self.names.len()).map(Color)
+    }
+
+    pub(super) fn name(&self, color: Color) -> &str {
+        &self.names[color.index()]
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub(super) fn is_declared(&self, color: Color) -> bool {
+        color.index() < self.known
+    }
+
+    // the colours that were not declared up front
+    pub(super) fn others(&self) -> impl Iterator<Item = Color> {
+        self.colors().skip(self.known)
+    }
+}
+
+// how the parser keeps the colour of a cube
+pub(super) trait ColorStorage {
+    type Key: PartialEq;
+
+    fn key(&mut self, name: &str) -> Result<Self::Key, PaletteError>;
+}
+impl ColorStorage for Palette {
+    type Key = Color;
+
+    fn key(&mut self, name: &str) -> Result<Color, PaletteError> {
+        self.resolve(name)
+    }
+}
+
+// every colour as its own `String`, the way draws were kept before colours
+// were interned
+pub(super) struct OwnedNames;
+impl ColorStorage for OwnedNames {
+    type Key = String;
+
+    fn key(&mut self, name: &str) -> Result<String, PaletteError> {
+        Ok(name.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette() {
+        let mut palette = Palette::standard();
+        let red = palette.get("red").unwrap();
+
+        assert_eq!(palette.resolve("red"), Ok(red));
+        assert_eq!(palette.others().count(), 0);
+        let gren = palette.resolve("gren").unwrap();
+        assert_eq!(palette.resolve("gren"), Ok(gren));
+        assert_eq!(palette.name(gren), "gren");
+        assert_eq!(palette.others().collect::<Vec<_>>(), vec![gren]);
+        assert!(palette.is_declared(red));
+        assert!(!palette.is_declared(gren));
+        assert_eq!(palette.len(), 4);
+        assert_eq!(gren.index(), 3);
+
+        let mut strict = Palette::standard().strict();
+        assert_eq!(strict.resolve("gren"), Err(PaletteError::UnknownColor));
+        assert_eq!(strict.resolve("blue").ok(), strict.get("blue"));
+        assert_eq!(strict.len(), 3);
+    }
+
+    #[test]
+    fn test_palette_is_bounded() {
+        // built by hand, as interning this many names one by one is quadratic
+        let names: Vec<String> = (0..=usize::from(u16::MAX))
+            .map(|idx| format!("c{idx}"))
+            .collect();
+        let mut palette = Palette {
+            known: names.len(),
+            names,
+            strict: false,
+        };
+
+        assert_eq!(
+            palette.colors().last().unwrap().index(),
+            usize::from(u16::MAX)
+        );
+        assert_eq!(palette.resolve("c7"), Ok(palette.get("c7").unwrap()));
+        assert_eq!(
+            palette.resolve("one more"),
+            Err(PaletteError::TooManyColors)
+        );
+        assert_eq!(palette.len(), usize::from(u16::MAX) + 1);
+    }
+}
@@ -1,7 +1,28 @@
-use std::collections::HashMap;
+use std::fmt;
+
+mod analysis;
+mod bag;
+mod bench;
+mod color;
+
+use bag::Bag;
+use color::{Color, ColorStorage, Palette, PaletteError};
 
 const INPUT: &str = include_str!("input.txt");
 
+pub(crate) fn run(args: &[String]) {
+    match args.first().map(String::as_str) {
+        None => main(),
+        Some("--bag" | "--bag-file") => bag::run(args),
+        Some("--likelihood" | "--most-likely") => analysis::run(args),
+        Some("--strict") => print_strict(INPUT),
+        Some("--powers") => print_powers(INPUT, &args[1..]),
+        Some("--colors") => print_colors(INPUT),
+        Some("--bench") => bench::run(&args[1..]),
+        Some(other) => eprintln!("unknown day02 option: {other}"),
+    }
+}
+
 pub fn main() {
     let sum = part_one(INPUT);
     println!("valid game id sum: {sum}");
@@ -9,125 +30,575 @@ pub fn main() {
     println!("powers sum: {sum}");
 }
 
-fn part_two(input: &str) -> u32 {
-    let games = parse_games(input);
-    let cube_powers = find_minimum_powers(&games);
+// both parts with colours outside red, green and blue rejected
+fn print_strict(input: &str) {
+    match parse_games(input, Palette::standard().strict()) {
+        Ok(games) => {
+            let valid_games = find_valid_games(&games, &Bag::standard());
+            let sum: u32 = valid_games.iter().map(|g| g.id).sum();
+            println!("valid game id sum: {sum}");
+            match find_minimum_powers(&games, Missing::Zero) {
+                Ok(powers) => println!("powers sum: {}", powers_sum(&powers)),
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+        Err(e) => eprintln!("{e}"),
+    }
+}
+
+fn print_colors(input: &str) {
+    match parse_games(input, Palette::standard()) {
+        Ok(games) => {
+            let others: Vec<&str> = games
+                .palette
+                .others()
+                .map(|color| games.palette.name(color))
+                .collect();
+            println!("{} colours", games.palette.len());
+            if !others.is_empty() {
+                println!("outside the standard palette: {}", others.join(", "));
+            }
+        }
+        Err(e) => eprintln!("{e}"),
+    }
+}
+
+// `--powers [--palette red,green,...] [--ignore-missing]`
+fn print_powers(input: &str, args: &[String]) {
+    let mut palette = Palette::standard();
+    let mut missing = Missing::Zero;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--palette" => {
+                let Some(names) = args.next() else {
+                    eprintln!("--palette expects comma separated colours");
+                    return;
+                };
+                let names: Vec<&str> = names.split(',').map(str::trim).collect();
+                // the parser only ever reads alphabetic colours
+                if let Some(name) = names
+                    .iter()
+                    .find(|name| name.is_empty() || !name.chars().all(char::is_alphabetic))
+                {
+                    eprintln!("invalid colour name {name:?}");
+                    return;
+                }
+                palette = match Palette::new(names) {
+                    Ok(palette) => palette,
+                    Err(e) => {
+                        eprintln!("invalid palette: {e}");
+                        return;
+                    }
+                };
+            }
+            "--ignore-missing" => missing = Missing::Ignore,
+            other => {
+                eprintln!("unknown powers option: {other}");
+                return;
+            }
+        }
+    }
+    match parse_games(input, palette) {
+        Ok(games) => match find_minimum_powers(&games, missing) {
+            Ok(powers) => println!("powers sum: {}", powers_sum(&powers)),
+            Err(e) => eprintln!("{e}"),
+        },
+        Err(e) => eprintln!("{e}"),
+    }
+}
+
+fn part_two(input: &str) -> u128 {
+    let games = parse_games(input, Palette::standard()).unwrap();
+    let cube_powers = find_minimum_powers(&games, Missing::Zero).unwrap();
+
+    powers_sum(&cube_powers)
+}
+
+// cannot overflow: that would take more than 2^64 games
+fn powers_sum(powers: &[u64]) -> u128 {
+    powers.iter().map(|power| u128::from(*power)).sum()
+}
+
+// a game whose power does not fit in a u64
+#[derive(Debug, PartialEq)]
+struct PowerOverflow {
+    id: u32,
+}
+impl fmt::Display for PowerOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the power of game {} overflows", self.id)
+    }
+}
 
-    cube_powers.iter().sum()
+// what a declared colour that a game never shows contributes to its power
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Missing {
+    // the game needs none of it, so its power is 0 (the puzzle's rule)
+    Zero,
+    // the power is the product of the colours shown only
+    Ignore,
 }
 
-fn find_minimum_powers(games: &[Game]) -> Vec<u32> {
+// the product, over the declared colours and any other colour a game shows,
+// of the fewest cubes of that colour the game could have been played with
+fn find_minimum_powers(games: &Games, missing: Missing) -> Result<Vec<u64>, PowerOverflow> {
+    let palette = &games.palette;
     let mut powers = Vec::new();
-    for game in games {
-        let mut min_counts: HashMap<&String, u32> = HashMap::new();
+    for game in &games.games {
+        // indexed by colour, `None` for colours left out of the product
+        let mut min_counts: Vec<Option<u32>> = palette
+            .colors()
+            .map(|color| (palette.is_declared(color) && missing == Missing::Zero).then_some(0))
+            .collect();
         for reveal in &game.cube_draws {
             for (color, count) in &reveal.colors {
-                let entry = min_counts.entry(color).or_insert(0);
+                let entry = min_counts[color.index()].get_or_insert(0);
                 *entry = (*entry).max(*count);
             }
         }
-        let power = min_counts.values().product();
+        let power = min_counts
+            .iter()
+            .flatten()
+            .try_fold(1u64, |power, count| power.checked_mul(u64::from(*count)))
+            .ok_or(PowerOverflow { id: game.id })?;
         powers.push(power);
     }
-    powers
+    Ok(powers)
 }
 
 fn part_one(input: &str) -> u32 {
-    let constraint: HashMap<String, u32> = [
-        ("red".to_string(), 12),
-        ("green".to_string(), 13),
-        ("blue".to_string(), 14),
-    ]
-    .into();
-    let games = parse_games(input);
-    let valid_games = find_valid_games(&games, &constraint);
+    let games = parse_games(input, Palette::standard()).unwrap();
+    let valid_games = find_valid_games(&games, &Bag::standard());
     valid_games.iter().map(|g| g.id).sum()
 }
 
-fn parse_games(input: &str) -> Vec<Game> {
-    input.lines().map(parse_line).collect()
+// the games of a record and the colours they use
+#[derive(Debug)]
+struct Games {
+    palette: Palette,
+    games: Vec<Game>,
+}
+
+fn parse_games(input: &str, mut palette: Palette) -> Result<Games, ParseError> {
+    let games = parse_lines(input, &mut palette)?;
+    Ok(Games { palette, games })
 }
 
-fn find_valid_games<'a>(games: &'a [Game], constraint: &'a HashMap<String, u32>) -> Vec<&'a Game> {
+// blank lines are skipped
+fn parse_lines<S: ColorStorage>(
+    input: &str,
+    storage: &mut S,
+) -> Result<Vec<Game<S::Key>>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| parse_line(idx + 1, line, storage))
+        .collect()
+}
+
+fn find_valid_games<'a>(games: &'a Games, bag: &Bag) -> Vec<&'a Game> {
+    let bag = bag.resolve(&games.palette);
     let mut valid = Vec::new();
-    for game in games {
-        if is_valid_game(game, constraint) {
+    for game in &games.games {
+        if bag.first_violation(game).is_none() {
             valid.push(game);
         }
     }
     valid
 }
 
-fn is_valid_game(game: &Game, constraints: &HashMap<String, u32>) -> bool {
-    for trekk in &game.cube_draws {
-        for (color, count) in &trekk.colors {
-            let constraint = constraints.get(color);
-            match constraint {
-                Some(c) if c >= count => {}
-                _ => return false,
+#[derive(Debug, PartialEq)]
+struct ParseError {
+    // both 1-based; the column counts bytes
+    line: usize,
+    column: usize,
+    kind: ParseErrorKind,
+    // what was there instead, `None` at the end of the line
+    found: Option<char>,
+}
+
+#[derive(Debug, PartialEq)]
+enum ParseErrorKind {
+    Expected(Expected),
+    NumberTooLarge,
+    DuplicateColor(String),
+    // only raised for a strict palette
+    UnknownColor(String),
+    TooManyColors,
+}
+
+#[derive(Debug, PartialEq)]
+enum Expected {
+    GameKeyword,
+    GameId,
+    Colon,
+    CubeCount,
+    Color,
+    // ',' between cubes or ';' between draws
+    Separator,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            ParseErrorKind::Expected(expected) => {
+                let expected = match expected {
+                    Expected::GameKeyword => "\"Game\"",
+                    Expected::GameId => "a game id",
+                    Expected::Colon => "':'",
+                    Expected::CubeCount => "a cube count",
+                    Expected::Color => "a colour",
+                    Expected::Separator => "',' or ';'",
+                };
+                write!(f, "expected {expected}")?;
             }
+            ParseErrorKind::NumberTooLarge => write!(f, "number too large")?,
+            ParseErrorKind::DuplicateColor(color) => {
+                write!(f, "{color} appears twice in one draw")?;
+            }
+            ParseErrorKind::UnknownColor(color) => write!(f, "unknown colour {color}")?,
+            ParseErrorKind::TooManyColors => write!(f, "{}", PaletteError::TooManyColors)?,
+        }
+        match self.found {
+            Some(c) => write!(f, ", found {c:?}"),
+            None => write!(f, ", found end of line"),
         }
     }
-    true
 }
 
-fn parse_line(line: &str) -> Game {
-    let (game_id, cube_draws) = line.split_once(':').unwrap();
-    let (_, id_str) = game_id.split_once(' ').unwrap();
-    let cube_draw_sets = cube_draws.split(':');
-    let mut cube_draws = Vec::new();
-    for reveal in cube_draw_sets {
-        let sets = reveal.split(';');
-        for set in sets {
-            let cubes = set.split(',');
-            let mut colors = HashMap::new();
-            for cube in cubes {
-                let (cube_count, cube_color) = cube.trim().split_once(' ').unwrap();
-                colors.insert(cube_color.to_owned(), cube_count.parse().unwrap());
-            }
-            let reveal = CubeDraw { colors };
-            cube_draws.push(reveal);
+struct Cursor<'a> {
+    line: usize,
+    text: &'a str,
+    pos: usize,
+}
+impl<'a> Cursor<'a> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn error_at(&self, pos: usize, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: pos + 1,
+            kind,
+            found: self.text[pos..].chars().next(),
+        }
+    }
+
+    fn expected(&self, expected: Expected) -> ParseError {
+        self.error_at(self.pos, ParseErrorKind::Expected(expected))
+    }
+
+    // `None` if the next character is not `c`
+    fn eat(&mut self, c: char) -> Option<()> {
+        self.text[self.pos..]
+            .starts_with(c)
+            .then(|| self.pos += c.len_utf8())
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let rest = &self.text[self.pos..];
+        let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn number(&mut self, expected: Expected) -> Result<u32, ParseError> {
+        let start = self.pos;
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        if digits.is_empty() {
+            return Err(self.expected(expected));
         }
+        digits
+            .parse()
+            .map_err(|_| self.error_at(start, ParseErrorKind::NumberTooLarge))
     }
+}
 
-    Game {
-        id: id_str.parse().unwrap(),
-        cube_draws,
+// "Game <id>: <count> <colour>, ...; ...", with any amount of whitespace
+// between tokens and an optional ';' at the end
+fn parse_line<S: ColorStorage>(
+    line_number: usize,
+    line: &str,
+    storage: &mut S,
+) -> Result<Game<S::Key>, ParseError> {
+    let mut cursor = Cursor {
+        line: line_number,
+        text: line,
+        pos: 0,
+    };
+    cursor.skip_whitespace();
+    if cursor.take_while(|c| c.is_alphabetic()) != "Game" {
+        return Err(cursor.error_at(
+            line.len() - line.trim_start().len(),
+            ParseErrorKind::Expected(Expected::GameKeyword),
+        ));
     }
+    cursor.skip_whitespace();
+    let id = cursor.number(Expected::GameId)?;
+    cursor.skip_whitespace();
+    cursor
+        .eat(':')
+        .ok_or_else(|| cursor.expected(Expected::Colon))?;
+
+    let mut cube_draws = Vec::new();
+    let mut colors: Vec<(S::Key, u32)> = Vec::new();
+    loop {
+        cursor.skip_whitespace();
+        let count = cursor.number(Expected::CubeCount)?;
+        cursor.skip_whitespace();
+        let color_start = cursor.pos;
+        let color = cursor.take_while(|c| c.is_alphabetic());
+        if color.is_empty() {
+            return Err(cursor.expected(Expected::Color));
+        }
+        let resolved = storage.key(color).map_err(|e| {
+            let kind = match e {
+                PaletteError::UnknownColor => ParseErrorKind::UnknownColor(color.to_owned()),
+                PaletteError::TooManyColors => ParseErrorKind::TooManyColors,
+            };
+            cursor.error_at(color_start, kind)
+        })?;
+        if colors.iter().any(|(seen, _)| *seen == resolved) {
+            return Err(cursor.error_at(
+                color_start,
+                ParseErrorKind::DuplicateColor(color.to_owned()),
+            ));
+        }
+        colors.push((resolved, count));
+
+        cursor.skip_whitespace();
+        if cursor.eat(',').is_some() {
+            continue;
+        }
+        let end_of_draw = cursor.eat(';').is_some();
+        cursor.skip_whitespace();
+        if !end_of_draw && cursor.peek().is_some() {
+            return Err(cursor.expected(Expected::Separator));
+        }
+        cube_draws.push(CubeDraw {
+            colors: std::mem::take(&mut colors),
+        });
+        if cursor.peek().is_none() {
+            break;
+        }
+    }
+
+    Ok(Game { id, cube_draws })
 }
 
-struct Game {
+#[derive(Debug)]
+struct Game<C = Color> {
     id: u32,
-    cube_draws: Vec<CubeDraw>,
+    cube_draws: Vec<CubeDraw<C>>,
 }
 
-struct CubeDraw {
-    colors: HashMap<String, u32>,
+#[derive(Debug)]
+struct CubeDraw<C = Color> {
+    // in the order written; a colour appears at most once
+    colors: Vec<(C, u32)>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn part_one_example() {
-        let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+    pub(super) const EXAMPLE_INPUT: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
 Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
 Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
 Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
 Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
-        let sum = part_one(input);
+
+    #[test]
+    fn part_one_example() {
+        let sum = part_one(EXAMPLE_INPUT);
         assert_eq!(sum, 8)
     }
 
     #[test]
     fn part_two_example() {
-        let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
-Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
-Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
-Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
-        let sum = part_two(input);
+        let sum = part_two(EXAMPLE_INPUT);
         assert_eq!(sum, 2286)
     }
+
+    fn parse_error(line: &str) -> ParseError {
+        match parse_line(1, line, &mut Palette::standard()) {
+            Ok(_) => panic!("{line} parsed"),
+            Err(e) => e,
+        }
+    }
+
+    fn expected(column: usize, expected: Expected, found: Option<char>) -> ParseError {
+        ParseError {
+            line: 1,
+            column,
+            kind: ParseErrorKind::Expected(expected),
+            found,
+        }
+    }
+
+    #[test]
+    fn parse_tolerates_whitespace_and_trailing_semicolon() {
+        let mut palette = Palette::standard();
+        let game = parse_line(1, "  Game   12 :3 blue ,4   red;\t1 red ;", &mut palette).unwrap();
+        let (red, blue) = (palette.get("red").unwrap(), palette.get("blue").unwrap());
+
+        assert_eq!(game.id, 12);
+        assert_eq!(game.cube_draws.len(), 2);
+        assert_eq!(game.cube_draws[0].colors, vec![(blue, 3), (red, 4)]);
+        assert_eq!(game.cube_draws[1].colors, vec![(red, 1)]);
+
+        let games = parse_games("Game 1: 1 red\n\n  \nGame 2: 2 blue\n", palette).unwrap();
+        assert_eq!(games.games.len(), 2);
+    }
+
+    #[test]
+    fn parse_errors_expected_token() {
+        assert_eq!(
+            parse_error("game 1: 3 blue"),
+            expected(1, Expected::GameKeyword, Some('g'))
+        );
+        assert_eq!(
+            parse_error("Game x: 3 blue"),
+            expected(6, Expected::GameId, Some('x'))
+        );
+        assert_eq!(
+            parse_error("Game 1 3 blue"),
+            expected(8, Expected::Colon, Some('3'))
+        );
+        assert_eq!(
+            parse_error("Game 1:"),
+            expected(8, Expected::CubeCount, None)
+        );
+        assert_eq!(
+            parse_error("Game 1: 3 blue;; 4 red"),
+            expected(16, Expected::CubeCount, Some(';'))
+        );
+        assert_eq!(
+            parse_error("Game 1: 3 , 4 red"),
+            expected(11, Expected::Color, Some(','))
+        );
+        assert_eq!(
+            parse_error("Game 1: 3 blue 4 red"),
+            expected(16, Expected::Separator, Some('4'))
+        );
+        assert_eq!(
+            parse_error("Game 1: 3 blue,"),
+            expected(16, Expected::CubeCount, None)
+        );
+    }
+
+    #[test]
+    fn parse_errors_invalid_values() {
+        assert_eq!(
+            parse_error("Game 1: 99999999999 blue"),
+            ParseError {
+                line: 1,
+                column: 9,
+                kind: ParseErrorKind::NumberTooLarge,
+                found: Some('9'),
+            }
+        );
+        assert_eq!(
+            parse_error("Game 1: 3 blue, 2 red, 1 blue"),
+            ParseError {
+                line: 1,
+                column: 26,
+                kind: ParseErrorKind::DuplicateColor("blue".to_string()),
+                found: Some('b'),
+            }
+        );
+    }
+
+    #[test]
+    fn minimum_powers_with_missing_colours() {
+        let input = "Game 1: 3 red, 2 green; 4 blue
+Game 2: 3 red; 5 green
+Game 3: 2 red, 7 purple";
+        let games = parse_games(input, Palette::standard()).unwrap();
+
+        assert_eq!(
+            find_minimum_powers(&games, Missing::Zero).unwrap(),
+            vec![24, 0, 0]
+        );
+        assert_eq!(
+            find_minimum_powers(&games, Missing::Ignore).unwrap(),
+            vec![24, 15, 14]
+        );
+
+        // only declared colours count as missing
+        let games = parse_games(input, Palette::new(["red"]).unwrap()).unwrap();
+        assert_eq!(
+            find_minimum_powers(&games, Missing::Zero).unwrap(),
+            vec![24, 15, 14]
+        );
+        let games = parse_games(
+            input,
+            Palette::new(["red", "green", "blue", "purple"]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            find_minimum_powers(&games, Missing::Zero).unwrap(),
+            vec![0, 0, 0]
+        );
+        assert_eq!(
+            find_minimum_powers(&games, Missing::Ignore).unwrap(),
+            vec![24, 15, 14]
+        );
+    }
+
+    #[test]
+    fn minimum_powers_overflow() {
+        // 4294967295^2 fits in a u64, a third factor does not
+        let input = "Game 1: 4294967295 red, 4294967295 green
+Game 2: 4294967295 red, 4294967295 green, 2 blue";
+        let games = parse_games(input, Palette::standard()).unwrap();
+
+        assert_eq!(
+            find_minimum_powers(&games, Missing::Ignore),
+            Err(PowerOverflow { id: 2 })
+        );
+        let games = parse_games(input.lines().next().unwrap(), Palette::standard()).unwrap();
+        let powers = find_minimum_powers(&games, Missing::Ignore).unwrap();
+        assert_eq!(powers, vec![18446744065119617025]);
+        assert_eq!(powers_sum(&[powers[0], powers[0]]), 36893488130239234050);
+    }
+
+    #[test]
+    fn parse_strict_palette() {
+        let typo = "Game 1: 1 red, 2 gren; 3 blue";
+        let lenient = parse_games(typo, Palette::standard()).unwrap();
+
+        assert_eq!(lenient.palette.others().count(), 1);
+        assert_eq!(
+            parse_games(typo, Palette::standard().strict()).unwrap_err(),
+            ParseError {
+                line: 1,
+                column: 18,
+                kind: ParseErrorKind::UnknownColor("gren".to_string()),
+                found: Some('g'),
+            }
+        );
+        assert!(parse_games(EXAMPLE_INPUT, Palette::standard().strict()).is_ok());
+    }
+
+    #[test]
+    fn parse_errors_report_line_numbers() {
+        let error = parse_games("Game 1: 1 red\n\nGame 2 1 red", Palette::standard()).unwrap_err();
+
+        assert_eq!(error.line, 3);
+        assert_eq!(error.column, 8);
+        assert_eq!(
+            error.to_string(),
+            "line 3, column 8: expected ':', found '1'"
+        );
+    }
 }
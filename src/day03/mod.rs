@@ -51,9 +51,9 @@ fn get_adjacent_parts(schematic: &mut Schematic) -> Vec<u32> {
                     first_idx = Some(col_idx);
                 }
             } else if let Some(first) = first_idx {
-                let min_row_idx = if row_idx <= 0 { 0 } else { row_idx - 1 };
+                let min_row_idx = if row_idx == 0 { 0 } else { row_idx - 1 };
                 let max_row_idx = (row_idx + 1).min(schematic.grid.len() - 1);
-                let min_col_idx = if first <= 0 { 0 } else { first - 1 };
+                let min_col_idx = if first == 0 { 0 } else { first - 1 };
                 let max_col_idx = (col_idx + 1).min(row.len() - 1);
                 'adjacent_check: for r_idx in min_row_idx..=max_row_idx {
                     for c_idx in min_col_idx..=max_col_idx {
@@ -100,6 +100,7 @@ struct Schematic {
 enum GridCell {
     Digit(u8),
     Space,
+    #[allow(dead_code)]
     Symbol(char),
 }
 
@@ -0,0 +1,199 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::bench::{report, time};
+
+use super::{
+    generator::{generate_almanac, GeneratorConfig, Layout},
+    normalise_ranges, Almanac, Map, MapResult, SeedRange,
+};
+
+const DEFAULT_RANGES_PER_MAP: u64 = 2000;
+const DEFAULT_SEEDS: u64 = 10_000;
+
+pub(super) fn run(args: &[String]) {
+    let values: Result<Vec<u64>, String> = args
+        .iter()
+        .map(|arg| {
+            arg.parse()
+                .map_err(|e| format!("could not parse {arg}: {e}"))
+        })
+        .collect();
+    let values = match values {
+        Ok(values) => values,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    let ranges_per_map = values.first().copied().unwrap_or(DEFAULT_RANGES_PER_MAP);
+    let seed_count = values.get(1).copied().unwrap_or(DEFAULT_SEEDS);
+
+    let almanac = generate_almanac(&bench_config(ranges_per_map, seed_count));
+    println!("{ranges_per_map} ranges per map, {seed_count} seeds");
+
+    let lookups = almanac.seeds.seeds().count() as u64;
+    let by_name = StringKeyed::new(&almanac);
+    let (linear, linear_time) = time(|| by_name.locations(&almanac, linear_find));
+    let (keyed, keyed_time) = time(|| by_name.locations(&almanac, binary_find));
+    let (interned, interned_time) = time(|| almanac.get_locations());
+    assert_eq!(linear, interned);
+    assert_eq!(keyed, interned);
+    report(
+        "point lookups, linear scan",
+        lookups,
+        "lookups",
+        linear_time,
+    );
+    report(
+        "point lookups, binary search",
+        lookups,
+        "lookups",
+        keyed_time,
+    );
+    report(
+        "point lookups, interned categories",
+        lookups,
+        "lookups",
+        interned_time,
+    );
+
+    let range_count = seed_count / 2;
+    let (queued, queued_time) = time(|| queued_location_ranges(&almanac));
+    let (swept, swept_time) = time(|| almanac.get_location_ranges());
+    assert_eq!(queued, swept);
+    report(
+        "range mapping, split queue",
+        range_count,
+        "ranges",
+        queued_time,
+    );
+    report(
+        "range mapping, single sweep",
+        range_count,
+        "ranges",
+        swept_time,
+    );
+}
+
+// seed ranges are kept short since the split queue degrades badly once a
+// range straddles many map ranges
+fn bench_config(ranges_per_map: u64, seed_count: u64) -> GeneratorConfig {
+    GeneratorConfig {
+        ranges_per_map,
+        max_value: ranges_per_map.max(1) * 1000,
+        seed_ranges: seed_count / 2,
+        max_seed_length: 100,
+        layout: Layout::Gaps,
+        ..Default::default()
+    }
+}
+
+// the lookups as they were before `Map` kept its ranges sorted and before
+// categories were interned
+
+fn linear_find(map: &Map, value: u64) -> Option<u64> {
+    map.ranges.iter().find_map(|range| range.convert(value))
+}
+
+fn binary_find(map: &Map, value: u64) -> Option<u64> {
+    map.find(value).map(|(_, converted)| converted)
+}
+
+// maps keyed by category name, paired with the name of their target
+struct StringKeyed<'a> {
+    maps: HashMap<String, (&'a Map, String)>,
+}
+impl<'a> StringKeyed<'a> {
+    fn new(almanac: &'a Almanac) -> Self {
+        let maps = almanac
+            .maps()
+            .map(|(from, map)| {
+                let to = almanac.name(map.to).to_string();
+                (almanac.name(from).to_string(), (map, to))
+            })
+            .collect();
+        StringKeyed { maps }
+    }
+
+    fn locations(&self, almanac: &Almanac, find: fn(&Map, u64) -> Option<u64>) -> Vec<u64> {
+        let mut locations = Vec::new();
+        for seed in almanac.seeds.seeds() {
+            let mut current = seed;
+            let mut next_map = "seed";
+            while let Some((map, to)) = self.maps.get(next_map) {
+                if let Some(converted) = find(map, current) {
+                    current = converted;
+                }
+                next_map = to;
+            }
+            locations.push(current);
+        }
+        locations
+    }
+}
+
+fn queued_split_range(map: &Map, seed_range: SeedRange) -> Vec<SeedRange> {
+    let mut splits = Vec::new();
+    let mut unmapped_set = VecDeque::new();
+    unmapped_set.push_front(seed_range);
+
+    while let Some(seed_range) = unmapped_set.pop_back() {
+        let mut mapped_set = Vec::new();
+
+        for map_range in &map.ranges {
+            for map_result in map_range.map_range(&seed_range) {
+                match map_result {
+                    MapResult::Mapped(mapped) => mapped_set.push(mapped),
+                    MapResult::Unmapped(unmapped) => {
+                        if unmapped != seed_range {
+                            unmapped_set.push_back(unmapped)
+                        }
+                    }
+                }
+            }
+        }
+
+        if mapped_set.is_empty() {
+            splits.push(seed_range);
+        } else {
+            splits.extend(mapped_set);
+        }
+    }
+    splits
+}
+
+fn queued_location_ranges(almanac: &Almanac) -> Vec<SeedRange> {
+    let mut seed_ranges = normalise_ranges(almanac.seeds.ranges());
+    for (_, map) in almanac.chain() {
+        let splits = seed_ranges
+            .into_iter()
+            .flat_map(|sr| queued_split_range(map, sr));
+        seed_ranges = normalise_ranges(splits);
+    }
+    seed_ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_old_and_new_lookups_agree() {
+        let almanac = generate_almanac(&bench_config(50, 200));
+
+        let by_name = StringKeyed::new(&almanac);
+
+        assert_eq!(
+            by_name.locations(&almanac, linear_find),
+            almanac.get_locations()
+        );
+        assert_eq!(
+            by_name.locations(&almanac, binary_find),
+            almanac.get_locations()
+        );
+        assert_eq!(
+            queued_location_ranges(&almanac),
+            almanac.get_location_ranges()
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        mpsc::{self, RecvTimeoutError},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::cli::option_value;
+
+use super::{parse_input, Almanac, SeedKind, SeedRange};
+
+// seeds handed to a worker at a time
+const CHUNK_SIZE: u64 = 1 << 20;
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy)]
+pub(super) struct BruteForceOptions {
+    pub(super) threads: usize,
+    // stop every worker as soon as a location below this is found
+    pub(super) cancel_below: Option<u64>,
+    pub(super) progress: bool,
+}
+impl Default for BruteForceOptions {
+    fn default() -> Self {
+        BruteForceOptions {
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            cancel_below: None,
+            progress: false,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) struct BruteForceResult {
+    pub(super) min_location: Option<u64>,
+    pub(super) seeds_evaluated: u64,
+    pub(super) cancelled: bool,
+}
+
+pub(super) fn run(input: &str, args: &[String]) {
+    let mut options = BruteForceOptions {
+        progress: true,
+        ..Default::default()
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let parsed = match arg.as_str() {
+            "--threads" => option_value(&mut args, arg).map(|val| options.threads = val),
+            "--cancel-below" => {
+                option_value(&mut args, arg).map(|val| options.cancel_below = Some(val))
+            }
+            "--quiet" => {
+                options.progress = false;
+                Ok(())
+            }
+            other => Err(format!("unknown brute force option: {other}")),
+        };
+        if let Err(e) = parsed {
+            eprintln!("{e}");
+            return;
+        }
+    }
+
+    let almanac = match parse_input(input, SeedKind::StartLength) {
+        Ok(almanac) => almanac,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    let instant = Instant::now();
+    let result = almanac.brute_force_min_location(options);
+    let elapsed = instant.elapsed();
+    match result.min_location {
+        Some(location) => println!("brute force: {location}"),
+        None => println!("brute force: no seeds"),
+    }
+    if result.cancelled {
+        println!("cancelled after finding a location below the bound");
+    }
+    println!(
+        "evaluated {} seeds in {} ms",
+        result.seeds_evaluated,
+        elapsed.as_millis()
+    );
+}
+
+impl Almanac {
+    pub(super) fn brute_force_min_location(&self, options: BruteForceOptions) -> BruteForceResult {
+        let chunks: Vec<SeedRange> = self
+            .seeds
+            .ranges()
+            .into_iter()
+            .flat_map(|sr| split_into_chunks(sr.start, sr.length))
+            .collect();
+        let total: u64 = chunks.iter().map(|sr| sr.length).sum();
+
+        let next_chunk = AtomicUsize::new(0);
+        let evaluated = AtomicU64::new(0);
+        let min_location = AtomicU64::new(u64::MAX);
+        let cancelled = AtomicBool::new(false);
+        // never sent on; the receiver disconnects once every worker is done
+        let (done_sender, done_receiver) = mpsc::channel::<()>();
+
+        thread::scope(|scope| {
+            for _ in 0..options.threads.max(1) {
+                let done_sender = done_sender.clone();
+                let work = || {
+                    while !cancelled.load(Ordering::Relaxed) {
+                        let Some(chunk) = chunks.get(next_chunk.fetch_add(1, Ordering::Relaxed))
+                        else {
+                            break;
+                        };
+                        let mut local_min = u64::MAX;
+                        for seed in chunk.start..=chunk.last() {
+                            local_min = local_min.min(self.get_location(seed));
+                        }
+                        min_location.fetch_min(local_min, Ordering::Relaxed);
+                        // releases the minimum above to the progress reader
+                        evaluated.fetch_add(chunk.length, Ordering::Release);
+                        if options.cancel_below.is_some_and(|bound| local_min < bound) {
+                            cancelled.store(true, Ordering::Relaxed);
+                        }
+                    }
+                };
+                scope.spawn(move || {
+                    work();
+                    drop(done_sender);
+                });
+            }
+            drop(done_sender);
+
+            if options.progress {
+                while done_receiver.recv_timeout(PROGRESS_INTERVAL)
+                    == Err(RecvTimeoutError::Timeout)
+                {
+                    let done = evaluated.load(Ordering::Acquire);
+                    // the minimum is only meaningful once a chunk has finished
+                    let best = match done {
+                        0 => "none".to_string(),
+                        _ => min_location.load(Ordering::Relaxed).to_string(),
+                    };
+                    eprintln!(
+                        "{done}/{total} seeds ({:.1}%), best so far {best}",
+                        done as f64 * 100.0 / total.max(1) as f64,
+                    );
+                }
+            }
+        });
+
+        let min_location = min_location.into_inner();
+        BruteForceResult {
+            min_location: (total > 0).then_some(min_location),
+            seeds_evaluated: evaluated.into_inner(),
+            cancelled: cancelled.into_inner(),
+        }
+    }
+}
+
+fn split_into_chunks(start: u64, length: u64) -> Vec<SeedRange> {
+    let mut chunks = Vec::new();
+    let mut offset = 0;
+    while offset < length {
+        let chunk_length = CHUNK_SIZE.min(length - offset);
+        chunks.push(SeedRange {
+            start: start + offset,
+            length: chunk_length,
+        });
+        offset += chunk_length;
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day05::{part_two, tests::EXAMPLE_INPUT};
+
+    #[test]
+    fn test_brute_force_matches_part_two() {
+        let almanac = parse_input(EXAMPLE_INPUT, SeedKind::StartLength).unwrap();
+        let result = almanac.brute_force_min_location(BruteForceOptions {
+            threads: 4,
+            ..Default::default()
+        });
+
+        assert_eq!(result.min_location, Some(part_two(EXAMPLE_INPUT)));
+        assert_eq!(result.seeds_evaluated, 27);
+        assert!(!result.cancelled);
+    }
+
+    #[test]
+    fn test_brute_force_cancels_below_bound() {
+        let almanac = parse_input(EXAMPLE_INPUT, SeedKind::StartLength).unwrap();
+        let result = almanac.brute_force_min_location(BruteForceOptions {
+            threads: 1,
+            cancel_below: Some(100),
+            progress: false,
+        });
+
+        assert!(result.cancelled);
+        assert!(result.min_location.unwrap() < 100);
+    }
+
+    #[test]
+    fn test_progress_waits_only_for_the_workers() {
+        let almanac = parse_input(EXAMPLE_INPUT, SeedKind::StartLength).unwrap();
+        let instant = Instant::now();
+        let result = almanac.brute_force_min_location(BruteForceOptions {
+            threads: 2,
+            cancel_below: None,
+            progress: true,
+        });
+
+        assert_eq!(result.seeds_evaluated, 27);
+        assert!(instant.elapsed() < PROGRESS_INTERVAL / 2);
+    }
+
+    #[test]
+    fn test_split_into_chunks() {
+        let chunks = split_into_chunks(5, 2 * CHUNK_SIZE + 3);
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[1].start, 5 + CHUNK_SIZE);
+        assert_eq!(chunks[2].length, 3);
+        assert!(split_into_chunks(5, 0).is_empty());
+    }
+}
@@ -0,0 +1,62 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(super) struct CategoryId(usize);
+impl CategoryId {
+    pub(super) fn index(self) -> usize {
+        self.0
+    }
+}
+
+// category names interned at parse time; ids are handed out in order of
+// first appearance
+#[derive(Debug, Default)]
+pub(super) struct Categories {
+    names: Vec<String>,
+}
+impl Categories {
+    pub(super) fn intern(&mut self, name: &str) -> CategoryId {
+        match self.get(name) {
+            Some(id) => id,
+            None => {
+                self.names.push(name.to_string());
+                CategoryId(self.names.len() - 1)
+            }
+        }
+    }
+
+    pub(super) fn get(&self, name: &str) -> Option<CategoryId> {
+        self.names.iter().position(|n| n == name).map(CategoryId)
+    }
+
+    pub(super) fn name(&self, id: CategoryId) -> &str {
+        &self.names[id.0]
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub(super) fn ids(&self) -> impl Iterator<Item = CategoryId> {
+        (0..self.names.len()).map(CategoryId)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern_categories() {
+        let mut categories = Categories::default();
+        let seed = categories.intern("seed");
+        let soil = categories.intern("soil");
+
+        assert_eq!(categories.intern("seed"), seed);
+        assert_ne!(seed, soil);
+        assert_eq!(categories.get("soil"), Some(soil));
+        assert_eq!(categories.get("water"), None);
+        assert_eq!(categories.name(soil), "soil");
+        assert_eq!(categories.len(), 2);
+        assert_eq!(categories.ids().collect::<Vec<_>>(), vec![seed, soil]);
+        assert_eq!(soil.index(), 1);
+    }
+}
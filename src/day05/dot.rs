@@ -0,0 +1,155 @@
+use std::collections::BTreeSet;
+
+use super::{parse_input, Almanac, Map, SeedKind};
+
+// total width in points of a map range bar in detailed mode
+const BAR_WIDTH: u64 = 200;
+
+pub(super) fn run(input: &str, args: &[String]) {
+    let detailed = match args.first().map(String::as_str) {
+        None => false,
+        Some("--detailed") => true,
+        Some(other) => {
+            eprintln!("unknown dot option: {other}");
+            return;
+        }
+    };
+    let almanac = match parse_input(input, SeedKind::Individual) {
+        Ok(almanac) => almanac,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    print!("{}", almanac.to_dot(detailed));
+}
+
+impl Almanac {
+    pub(super) fn to_dot(&self, detailed: bool) -> String {
+        let mut maps: Vec<(&str, &str, &Map)> = self
+            .maps()
+            .map(|(from, map)| (self.name(from), self.name(map.to), map))
+            .collect();
+        maps.sort_by_key(|(from, _, _)| *from);
+
+        let categories: BTreeSet<&str> =
+            maps.iter().flat_map(|(from, to, _)| [*from, *to]).collect();
+
+        let mut out = String::from("digraph almanac {\n    rankdir=LR;\n");
+        for category in &categories {
+            out += &format!("    \"{category}\" [shape=ellipse];\n");
+        }
+        for (from, to, map) in maps {
+            let label = match map.ranges.len() {
+                1 => "1 range".to_string(),
+                count => format!("{count} ranges"),
+            };
+            if detailed {
+                let node = format!("{from}-to-{to}");
+                out += &format!(
+                    "    \"{node}\" [shape=plain, label=<{}>];\n",
+                    range_table(&node, map)
+                );
+                out += &format!("    \"{from}\" -> \"{node}\" [label=\"{label}\"];\n");
+                out += &format!("    \"{node}\" -> \"{to}\";\n");
+            } else {
+                out += &format!("    \"{from}\" -> \"{to}\" [label=\"{label}\"];\n");
+            }
+        }
+        out += "}\n";
+        out
+    }
+}
+
+// one row per map range: its input line, source and destination intervals,
+// and a bar placing the source interval within the span covered by the map
+fn range_table(title: &str, map: &Map) -> String {
+    let span_start = map.ranges.iter().map(|r| r.source).min().unwrap_or(0);
+    let span_last = map
+        .ranges
+        .iter()
+        .map(|r| r.last_source())
+        .max()
+        .unwrap_or(0);
+    let span = (span_last - span_start) as f64 + 1.0;
+    let scale = |value: u64| (value as f64 / span * BAR_WIDTH as f64).round() as u64;
+
+    let mut table = format!(
+        "<TABLE BORDER=\"0\" CELLBORDER=\"1\" CELLSPACING=\"0\"><TR><TD COLSPAN=\"4\"><B>{title}</B></TD></TR>"
+    );
+    for (range, line) in map.ranges.iter().zip(&map.range_lines) {
+        let before = scale(range.source - span_start);
+        let inside = scale(range.length).max(1);
+        let after = BAR_WIDTH.saturating_sub(before + inside);
+
+        let mut bar = String::new();
+        for (width, color) in [(before, "white"), (inside, "steelblue"), (after, "white")] {
+            if width > 0 {
+                bar += &format!(
+                    "<TD WIDTH=\"{width}\" HEIGHT=\"10\" FIXEDSIZE=\"TRUE\" BGCOLOR=\"{color}\"></TD>"
+                );
+            }
+        }
+        table += &format!(
+            "<TR><TD>line {line}</TD><TD>{}..={}</TD><TD>{}..={}</TD><TD><TABLE BORDER=\"0\" CELLBORDER=\"0\" CELLSPACING=\"0\"><TR>{bar}</TR></TABLE></TD></TR>",
+            range.source,
+            range.last_source(),
+            range.dest,
+            range.last_dest(),
+        );
+    }
+    table += "</TABLE>";
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day05::tests::EXAMPLE_INPUT;
+
+    #[test]
+    fn test_dot_graph() {
+        let almanac = parse_input(EXAMPLE_INPUT, SeedKind::Individual).unwrap();
+        let dot = almanac.to_dot(false);
+
+        assert!(dot.starts_with("digraph almanac {"));
+        assert_eq!(dot.matches("[shape=ellipse]").count(), 8);
+        assert_eq!(dot.matches(" -> ").count(), 7);
+        assert!(dot.contains("\"seed\" -> \"soil\" [label=\"2 ranges\"];"));
+        assert!(dot.contains("\"fertilizer\" -> \"water\" [label=\"4 ranges\"];"));
+        assert_eq!(dot, almanac.to_dot(false));
+    }
+
+    #[test]
+    fn test_dot_graph_detailed() {
+        let almanac = parse_input(EXAMPLE_INPUT, SeedKind::Individual).unwrap();
+        let dot = almanac.to_dot(true);
+
+        assert_eq!(dot.matches(" -> ").count(), 14);
+        assert!(dot.contains("\"seed\" -> \"seed-to-soil\" [label=\"2 ranges\"];"));
+        assert!(dot.contains("\"seed-to-soil\" -> \"soil\";"));
+        assert!(dot.contains("<TD>line 4</TD><TD>98..=99</TD><TD>50..=51</TD>"));
+        // one row per map range across all maps
+        assert_eq!(dot.matches("<TD>line ").count(), 18);
+    }
+
+    #[test]
+    fn test_dot_graph_branching() {
+        let almanac = parse_input(
+            "seeds: 1
+
+seed-to-soil map:
+1 2 3
+
+water-to-soil map:
+4 5 6",
+            SeedKind::Individual,
+        )
+        .unwrap();
+        let dot = almanac.to_dot(false);
+
+        assert_eq!(dot.matches("[shape=ellipse]").count(), 3);
+        assert!(dot.contains("\"seed\" -> \"soil\" [label=\"1 range\"];"));
+        assert!(dot.contains("\"water\" -> \"soil\" [label=\"1 range\"];"));
+    }
+}
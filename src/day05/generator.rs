@@ -0,0 +1,284 @@
+use super::{Almanac, MapRange, SeedRange, SeedSpec};
+use crate::{cli::option_value, rng::Rng};
+
+const STANDARD_CATEGORIES: [&str; 8] = [
+    "seed",
+    "soil",
+    "fertilizer",
+    "water",
+    "light",
+    "temperature",
+    "humidity",
+    "location",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Layout {
+    // source ranges tile the value space without gaps
+    Contiguous,
+    // source ranges with unmapped gaps of up to a quarter slot between them
+    Gaps,
+    // tiny source ranges scattered through the value space
+    Fragmented,
+}
+impl Layout {
+    fn parse(name: &str) -> Option<Layout> {
+        match name {
+            "contiguous" => Some(Layout::Contiguous),
+            "gaps" => Some(Layout::Gaps),
+            "fragmented" => Some(Layout::Fragmented),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(super) struct GeneratorConfig {
+    pub(super) seed: u64,
+    // including "seed" and "location", so there is one map fewer
+    pub(super) categories: usize,
+    pub(super) ranges_per_map: u64,
+    // every generated interval ends at or below this value
+    pub(super) max_value: u64,
+    pub(super) seed_ranges: u64,
+    pub(super) max_seed_length: u64,
+    pub(super) layout: Layout,
+}
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        GeneratorConfig {
+            seed: 2023,
+            categories: STANDARD_CATEGORIES.len(),
+            ranges_per_map: 40,
+            max_value: u64::from(u32::MAX),
+            seed_ranges: 10,
+            max_seed_length: u64::from(u32::MAX) / 10,
+            layout: Layout::Gaps,
+        }
+    }
+}
+impl GeneratorConfig {
+    pub(super) fn check(&self) -> Result<(), String> {
+        if self.categories < 2 {
+            return Err("an almanac needs at least two categories".to_string());
+        }
+        if self.max_value == 0 {
+            return Err("--max-value must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+pub(super) fn run(args: &[String]) {
+    let mut config = GeneratorConfig::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let parsed = match arg.as_str() {
+            "--seed" => option_value(&mut args, arg).map(|val| config.seed = val),
+            "--categories" => option_value(&mut args, arg).map(|val| config.categories = val),
+            "--ranges" => option_value(&mut args, arg).map(|val| config.ranges_per_map = val),
+            "--max-value" => option_value(&mut args, arg).map(|val| config.max_value = val),
+            "--seed-ranges" => option_value(&mut args, arg).map(|val| config.seed_ranges = val),
+            "--max-seed-length" => {
+                option_value(&mut args, arg).map(|val| config.max_seed_length = val)
+            }
+            "--layout" => option_value::<String>(&mut args, arg).and_then(|val| {
+                let layout = Layout::parse(&val).ok_or_else(|| {
+                    format!("unknown layout {val}, expected contiguous, gaps or fragmented")
+                })?;
+                config.layout = layout;
+                Ok(())
+            }),
+            other => Err(format!("unknown generator option: {other}")),
+        };
+        if let Err(e) = parsed {
+            eprintln!("{e}");
+            return;
+        }
+    }
+    if let Err(e) = config.check() {
+        eprintln!("{e}");
+        return;
+    }
+    print!("{}", generate_almanac(&config));
+}
+
+// panics on a config that `check` rejects
+pub(super) fn generate_almanac(config: &GeneratorConfig) -> Almanac {
+    if let Err(e) = config.check() {
+        panic!("{e}");
+    }
+    let mut rng = Rng::new(config.seed);
+
+    let names = category_names(config.categories);
+    let mut maps = Vec::new();
+    for pair in names.windows(2) {
+        maps.push((&pair[0], &pair[1], generate_map(&mut rng, config)));
+    }
+
+    let mut seed_ranges = Vec::new();
+    for _ in 0..config.seed_ranges {
+        let start = rng.gen_range(0..config.max_value);
+        let max_length = config.max_seed_length.clamp(1, config.max_value - start);
+        seed_ranges.push(SeedRange {
+            start,
+            length: rng.gen_range_inclusive(1..=max_length),
+        });
+    }
+    let mut almanac = Almanac::new(SeedSpec::StartLength(seed_ranges));
+    for (from, to, ranges) in maps {
+        let lines = (1..=ranges.len()).collect();
+        almanac
+            .insert_map(from, to, ranges, lines)
+            .expect("generated source ranges are disjoint");
+    }
+    almanac
+}
+
+fn category_names(count: usize) -> Vec<String> {
+    if count == STANDARD_CATEGORIES.len() {
+        return STANDARD_CATEGORIES.iter().map(|c| c.to_string()).collect();
+    }
+    (0..count)
+        .map(|idx| match idx {
+            0 => "seed".to_string(),
+            _ if idx == count - 1 => "location".to_string(),
+            _ => format!("category{idx}"),
+        })
+        .collect()
+}
+
+// one range per slot of the value space, so source ranges never overlap
+fn generate_map(rng: &mut Rng, config: &GeneratorConfig) -> Vec<MapRange> {
+    let slots = config.ranges_per_map.clamp(1, config.max_value);
+    let slot = config.max_value / slots;
+    let mut ranges = Vec::new();
+    for idx in 0..config.ranges_per_map.min(slots) {
+        let slot_start = idx * slot;
+        let (offset, length) = match config.layout {
+            Layout::Contiguous => (0, slot),
+            Layout::Gaps => {
+                let offset = rng.gen_range_inclusive(0..=slot / 4);
+                (offset, rng.gen_range_inclusive(1..=slot - offset))
+            }
+            Layout::Fragmented => {
+                let length = rng.gen_range_inclusive(1..=(slot / 64).max(1));
+                (rng.gen_range_inclusive(0..=slot - length), length)
+            }
+        };
+        ranges.push(MapRange {
+            source: slot_start + offset,
+            dest: rng.gen_range_inclusive(0..=config.max_value - length),
+            length,
+        });
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day05::{parse_input, SeedKind};
+
+    fn assert_valid(almanac: &Almanac, config: &GeneratorConfig) {
+        assert_eq!(almanac.maps().count(), config.categories - 1);
+        assert_eq!(almanac.seeds.ranges().len() as u64, config.seed_ranges);
+        for (_, map) in almanac.maps() {
+            for pair in map.ranges.windows(2) {
+                assert!(pair[0].source + pair[0].length <= pair[1].source);
+            }
+            for range in &map.ranges {
+                assert!(range.length > 0);
+                assert!(range.length <= config.max_value - range.source);
+                assert!(range.length <= config.max_value - range.dest);
+            }
+        }
+        for sr in almanac.seeds.ranges() {
+            assert!(sr.length > 0);
+            assert!(sr.length <= config.max_value - sr.start);
+        }
+    }
+
+    #[test]
+    fn test_generator_is_reproducible() {
+        let config = GeneratorConfig::default();
+        let first = generate_almanac(&config);
+
+        assert_eq!(first, generate_almanac(&config));
+        assert_ne!(
+            first,
+            generate_almanac(&GeneratorConfig {
+                seed: config.seed + 1,
+                ..config
+            })
+        );
+        assert_eq!(
+            parse_input(&first.to_string(), SeedKind::StartLength).unwrap(),
+            first
+        );
+    }
+
+    #[test]
+    fn test_generator_layouts() {
+        for layout in [Layout::Contiguous, Layout::Gaps, Layout::Fragmented] {
+            let config = GeneratorConfig {
+                categories: 4,
+                ranges_per_map: 100,
+                layout,
+                ..Default::default()
+            };
+            let almanac = generate_almanac(&config);
+            assert_valid(&almanac, &config);
+            let category2 = almanac.categories.get("category2").unwrap();
+            let map = almanac.map_from(category2).unwrap();
+            assert_eq!(almanac.name(map.to), "location");
+        }
+
+        let contiguous = generate_almanac(&GeneratorConfig {
+            layout: Layout::Contiguous,
+            ..Default::default()
+        });
+        for (_, map) in contiguous.maps() {
+            for pair in map.ranges.windows(2) {
+                assert_eq!(pair[0].source + pair[0].length, pair[1].source);
+            }
+        }
+    }
+
+    #[test]
+    fn test_config_check() {
+        assert_eq!(GeneratorConfig::default().check(), Ok(()));
+        let config = GeneratorConfig {
+            categories: 1,
+            ..Default::default()
+        };
+        assert!(config.check().is_err());
+        let config = GeneratorConfig {
+            max_value: 0,
+            ..Default::default()
+        };
+        assert!(config.check().is_err());
+    }
+
+    #[test]
+    fn test_generator_near_u64_max() {
+        let config = GeneratorConfig {
+            max_value: u64::MAX,
+            max_seed_length: u64::MAX,
+            ranges_per_map: 16,
+            ..Default::default()
+        };
+        let almanac = generate_almanac(&config);
+        assert_valid(&almanac, &config);
+
+        let location_ranges = almanac.get_location_ranges();
+        for sr in almanac.seeds.ranges() {
+            for seed in [sr.start, sr.last()] {
+                let location = almanac.get_location(seed);
+                assert!(location_ranges
+                    .iter()
+                    .any(|sr| sr.start <= location && location <= sr.last()));
+            }
+        }
+    }
+}
@@ -1,10 +1,44 @@
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
+    fmt, fs,
+    io::{self, Read},
     time::Instant,
 };
 
+mod bench;
+mod brute_force;
+mod category;
+mod dot;
+mod generator;
+mod num;
+#[cfg(test)]
+mod proptest;
+mod report;
+mod seeds;
+mod trace;
+
+use category::{Categories, CategoryId};
+use num::Unsigned;
+use seeds::{SeedError, SeedKind, SeedSpec};
+
 const INPUT: &str = include_str!("input.txt");
 
+pub(crate) fn run(args: &[String]) {
+    match args.first().map(String::as_str) {
+        None => main(),
+        Some("--trace") => trace::print_traces(INPUT, &args[1..]),
+        Some("--stats") => print_stage_stats(INPUT),
+        Some("--brute-force") => brute_force::run(INPUT, &args[1..]),
+        Some("--bench") => bench::run(&args[1..]),
+        Some("--dot") => dot::run(INPUT, &args[1..]),
+        Some("fmt") => format_almanac(args.get(1)),
+        Some("--generate") => generator::run(&args[1..]),
+        Some("--seeds") => print_lowest_location(INPUT, args.get(1)),
+        Some("--report") => report::run(INPUT, &args[1..]),
+        Some("--u128") => print_wide(INPUT),
+        Some(other) => eprintln!("unknown day05 option: {other}"),
+    }
+}
+
 pub(crate) fn main() {
     let location = part_one(INPUT);
     println!("part one: {location}");
@@ -15,284 +49,633 @@ pub(crate) fn main() {
     println!("completed in {} µs", elapsed.as_micros());
 }
 
+fn print_lowest_location(input: &str, kind: Option<&String>) {
+    let Some(kind) = kind.and_then(|kind| SeedKind::parse(kind)) else {
+        eprintln!("--seeds expects individual, pairs or inclusive");
+        return;
+    };
+    match lowest_location::<u64>(input, kind) {
+        Ok(location) => println!("lowest location: {location}"),
+        Err(e) => eprintln!("{e}"),
+    }
+}
+
+// same answers as `main`, computed with 128-bit arithmetic
+fn print_wide(input: &str) {
+    for (part, kind) in [
+        ("one", SeedKind::Individual),
+        ("two", SeedKind::StartLength),
+    ] {
+        match lowest_location::<u128>(input, kind) {
+            Ok(location) => println!("part {part}: {location}"),
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+}
+
+fn print_stage_stats(input: &str) {
+    let almanac = match parse_input(input, SeedKind::StartLength) {
+        Ok(almanac) => almanac,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    let (location_ranges, stats) = almanac.get_location_ranges_with_stats();
+    for stage in stats {
+        println!(
+            "{}-to-{}: {} ranges, {} after merge",
+            stage.from, stage.to, stage.before_merge, stage.after_merge
+        );
+    }
+    println!("location ranges: {}", location_ranges.len());
+}
+
+// prints the almanac at `path` (or stdin) in canonical form
+fn format_almanac(path: Option<&String>) {
+    let input = match path {
+        Some(path) => fs::read_to_string(path).map_err(|e| format!("could not read {path}: {e}")),
+        None => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .map(|_| input)
+                .map_err(|e| format!("could not read stdin: {e}"))
+        }
+    };
+    let input = match input {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    match parse_input(&input, SeedKind::Individual) {
+        Ok(almanac) => print!("{almanac}"),
+        Err(e) => eprintln!("{e}"),
+    }
+}
+
 fn part_one(input: &str) -> u64 {
-    let almanac = parse_input(input);
-    let mut locations: Vec<u64> = almanac.get_locations();
-    locations.sort();
-    locations[0]
+    lowest_location(input, SeedKind::Individual).unwrap()
 }
 
 fn part_two(input: &str) -> u64 {
-    let almanac = parse_input(input);
+    lowest_location(input, SeedKind::StartLength).unwrap()
+}
+
+// both parts go through the same range mapping; only the seed line differs
+fn lowest_location<N: Unsigned>(input: &str, kind: SeedKind) -> Result<N, AlmanacError<N>> {
+    let almanac = parse_almanac::<N>(input, kind)?;
     let seed_ranges = almanac.get_location_ranges();
 
     seed_ranges
         .into_iter()
         .min_by_key(|sr| sr.start)
         .map(|sr| sr.start)
-        .unwrap()
+        .ok_or(AlmanacError::NoSeeds)
 }
 
-fn parse_input(input: &str) -> Almanac {
-    let mut lines = input.lines();
-    let seeds: Vec<u64> = lines
-        .next()
-        .unwrap()
-        .split_once(": ")
-        .unwrap()
-        .1
-        .trim()
-        .split(' ')
-        .map(|num| {
-            num.parse::<u64>()
-                .map_err(|e| format!("could not parse {num}: {e}"))
-                .unwrap()
+#[derive(Debug, PartialEq)]
+enum AlmanacError<N = u64> {
+    Seeds(SeedError<N>),
+    NoSeeds,
+    EmptyInput,
+    // the first line has no "seeds:" label
+    MissingSeedsLabel { line: usize },
+    // a map header that is not "<from>-to-<to> map:"
+    MalformedHeader { line: usize, header: String },
+    // a token that is not a number, or does not fit the number type
+    InvalidNumber { line: usize, token: String },
+    // a map line without exactly three numbers
+    MalformedRange { line: usize, values: usize },
+    EmptyRange { line: usize },
+    // the source or destination interval runs past the largest value
+    RangeOverflow { line: usize },
+    // two ranges of one map share source values, lines in input order
+    OverlappingRanges { line: usize, other: usize },
+}
+impl<N: fmt::Display> fmt::Display for AlmanacError<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlmanacError::Seeds(e) => write!(f, "invalid seeds: {e}"),
+            AlmanacError::NoSeeds => write!(f, "the almanac has no seeds"),
+            AlmanacError::EmptyInput => write!(f, "the almanac is empty"),
+            AlmanacError::MissingSeedsLabel { line } => {
+                write!(f, "line {line}: expected \"seeds:\" followed by numbers")
+            }
+            AlmanacError::MalformedHeader { line, header } => {
+                write!(
+                    f,
+                    "line {line}: expected <from>-to-<to> map:, got {header:?}"
+                )
+            }
+            AlmanacError::InvalidNumber { line, token } => {
+                write!(f, "line {line}: {token} is not a valid number")
+            }
+            AlmanacError::MalformedRange { line, values } => {
+                write!(f, "line {line}: expected 3 numbers, got {values}")
+            }
+            AlmanacError::EmptyRange { line } => write!(f, "line {line}: map range is empty"),
+            AlmanacError::RangeOverflow { line } => {
+                write!(f, "line {line}: map range runs past the largest value")
+            }
+            AlmanacError::OverlappingRanges { line, other } => {
+                write!(f, "line {other}: map range overlaps the one on line {line}")
+            }
+        }
+    }
+}
+impl<N> From<SeedError<N>> for AlmanacError<N> {
+    fn from(e: SeedError<N>) -> Self {
+        AlmanacError::Seeds(e)
+    }
+}
+
+fn parse_input(input: &str, kind: SeedKind) -> Result<Almanac, AlmanacError> {
+    parse_almanac(input, kind)
+}
+
+fn parse_numbers<N: Unsigned>(line_number: usize, line: &str) -> Result<Vec<N>, AlmanacError<N>> {
+    line.split_whitespace()
+        .map(|token| {
+            token.parse::<N>().map_err(|_| AlmanacError::InvalidNumber {
+                line: line_number,
+                token: token.to_string(),
+            })
         })
-        .collect();
+        .collect()
+}
 
-    let mut maps = HashMap::new();
+fn parse_almanac<N: Unsigned>(input: &str, kind: SeedKind) -> Result<Almanac<N>, AlmanacError<N>> {
+    let mut lines = input.lines().enumerate().map(|(idx, line)| (idx + 1, line));
+    let (seed_line, seeds) = lines.next().ok_or(AlmanacError::EmptyInput)?;
+    let (_, seeds) = seeds
+        .split_once(':')
+        .ok_or(AlmanacError::MissingSeedsLabel { line: seed_line })?;
+    let seed_values = parse_numbers(seed_line, seeds)?;
 
-    while let Some(line) = lines.next() {
-        if line.is_empty() {
+    let seeds = SeedSpec::new(seed_values, kind)?;
+    let mut almanac = Almanac::new(seeds);
+
+    while let Some((header_line, line)) = lines.next() {
+        if line.trim().is_empty() {
             continue;
         }
 
-        let (from, to) = line.split_once(' ').unwrap().0.split_once("-to-").unwrap();
+        let Some((from, to)) = line
+            .split_whitespace()
+            .next()
+            .and_then(|header| header.split_once("-to-"))
+            .filter(|(from, to)| !from.is_empty() && !to.is_empty())
+        else {
+            return Err(AlmanacError::MalformedHeader {
+                line: header_line,
+                header: line.trim().to_string(),
+            });
+        };
 
-        let mut ranges: Vec<MapRange> = Vec::new();
-        for range_line in lines.by_ref() {
-            if range_line.is_empty() {
+        let mut ranges: Vec<MapRange<N>> = Vec::new();
+        let mut range_lines: Vec<usize> = Vec::new();
+        for (line_number, range_line) in lines.by_ref() {
+            if range_line.trim().is_empty() {
                 break;
             }
-            let range_vals: Vec<u64> = range_line
-                .split(' ')
-                .map(|n| n.parse::<u64>().unwrap())
-                .collect();
+            let range_vals = parse_numbers(line_number, range_line)?;
+            let &[dest, source, length] = range_vals.as_slice() else {
+                return Err(AlmanacError::MalformedRange {
+                    line: line_number,
+                    values: range_vals.len(),
+                });
+            };
+            if length == N::ZERO {
+                return Err(AlmanacError::EmptyRange { line: line_number });
+            }
+            let offset = length - N::ONE;
+            if source.checked_add(offset).is_none() || dest.checked_add(offset).is_none() {
+                return Err(AlmanacError::RangeOverflow { line: line_number });
+            }
 
             ranges.push(MapRange {
-                source: range_vals[1],
-                dest: range_vals[0],
-                length: range_vals[2],
-            })
+                source,
+                dest,
+                length,
+            });
+            range_lines.push(line_number);
         }
 
-        let parsed = Map {
-            to: to.to_string(),
-            ranges,
-        };
-        maps.insert(from.to_string(), parsed);
+        almanac.insert_map(from, to, ranges, range_lines)?;
     }
 
-    Almanac { seeds, maps }
+    Ok(almanac)
 }
 
+// `length` is never zero and neither interval runs past `N::MAX`; the parser
+// rejects ranges that break this, so the arithmetic below cannot overflow
 #[derive(Debug, Clone, Copy, PartialEq)]
-struct MapRange {
-    source: u64,
-    dest: u64,
-    length: u64,
+struct MapRange<N = u64> {
+    source: N,
+    dest: N,
+    length: N,
 }
-impl MapRange {
-    fn convert(&self, current: u64) -> Option<u64> {
-        let big_boi = self.source + self.length;
-        if current >= self.source && current < big_boi {
-            let res = (current - self.source) + self.dest;
-            return Some(res);
+impl<N: Unsigned> MapRange<N> {
+    fn convert(&self, current: N) -> Option<N> {
+        if current >= self.source && current - self.source < self.length {
+            return Some(self.dest + (current - self.source));
         }
         None
     }
-    fn last_source(&self) -> u64 {
-        self.source + self.length - 1
-    }
-
-    fn map_range(&self, seed_range: &SeedRange) -> Vec<MapResult> {
-        let mut res: Vec<MapResult> = Vec::new();
-        if seed_range.start < self.source {
-            if seed_range.last() < self.source {
-                // no map, seed_range precedes map range
-                return vec![MapResult::Unmapped(*seed_range)];
-            }
 
-            if let Some(mapped_end) = self.convert(seed_range.last()) {
-                // end of range is within map range
-                let before_length = self.source - seed_range.start;
-                if before_length > 0 {
-                    res.push(MapResult::Unmapped(SeedRange {
-                        start: seed_range.start,
-                        length: before_length,
-                    }));
-                }
-
-                let in_length = mapped_end - self.dest + 1;
-                if in_length > 0 {
-                    res.push(MapResult::Mapped(SeedRange {
-                        start: self.dest,
-                        length: in_length,
-                    }));
-                }
-            } else {
-                // map range intersects completely with seed_range
-                let before_length = self.source - seed_range.start;
-                if before_length > 0 {
-                    res.push(MapResult::Unmapped(SeedRange {
-                        start: seed_range.start,
-                        length: before_length,
-                    }));
-                }
-
-                res.push(MapResult::Mapped(SeedRange {
-                    start: self.dest,
-                    length: self.length,
-                }));
-
-                let after_length = seed_range.length - (before_length + self.length);
-                if after_length > 0 {
-                    res.push(MapResult::Unmapped(SeedRange {
-                        start: self.source + self.length,
-                        length: after_length,
-                    }));
-                }
-            }
-        } else if let Some(mapped_start) = self.convert(seed_range.start) {
-            // seed start is inside map range
-            if let Some(mapped_end) = self.convert(seed_range.last()) {
-                // seed_range is included in map range
-                let length = (mapped_end - mapped_start) + 1;
-                if length > 0 {
-                    res.push(MapResult::Mapped(SeedRange {
-                        start: mapped_start,
-                        length,
-                    }));
-                }
-            } else {
-                // seed range starts in map, and ends outside of map
-
-                let in_length = (self.dest + self.length) - mapped_start;
-                if in_length > 0 {
-                    res.push(MapResult::Mapped(SeedRange {
-                        start: mapped_start,
-                        length: in_length,
-                    }));
-                }
+    fn last_source(&self) -> N {
+        self.source + (self.length - N::ONE)
+    }
 
-                let out_length = seed_range.length - in_length;
-                if out_length > 0 {
-                    let out_range = SeedRange {
-                        start: self.last_source() + 1,
-                        length: out_length,
-                    };
-                    res.push(MapResult::Unmapped(out_range));
-                }
-            }
-        } else {
-            // no map, seed_range is fully after map range
+    fn last_dest(&self) -> N {
+        self.dest + (self.length - N::ONE)
+    }
+}
+impl<N: Unsigned> MapRange<N> {
+    // splits `seed_range` against this range alone; `Map::split_range` has
+    // replaced it, and the bench keeps it as the baseline
+    fn map_range(&self, seed_range: &SeedRange<N>) -> Vec<MapResult<N>> {
+        let last = seed_range.last();
+        let source_last = self.last_source();
+        if last < self.source || seed_range.start > source_last {
+            // no overlap, the seed range is entirely before or after the map
             return vec![MapResult::Unmapped(*seed_range)];
         }
 
+        let mut res = Vec::new();
+        if seed_range.start < self.source {
+            res.push(MapResult::Unmapped(SeedRange {
+                start: seed_range.start,
+                length: self.source - seed_range.start,
+            }));
+        }
+
+        // the overlap is no longer than either range, so its length fits
+        let overlap_start = seed_range.start.max(self.source);
+        let overlap_last = last.min(source_last);
+        res.push(MapResult::Mapped(SeedRange {
+            start: self.dest + (overlap_start - self.source),
+            length: (overlap_last - overlap_start) + N::ONE,
+        }));
+
+        // `source_last` is below `last` here, so the next value exists
+        if let Some(after) = source_last
+            .checked_add(N::ONE)
+            .filter(|_| last > source_last)
+        {
+            res.push(MapResult::Unmapped(SeedRange {
+                start: after,
+                length: last - source_last,
+            }));
+        }
         res
     }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
-enum MapResult {
-    Mapped(SeedRange),
-    Unmapped(SeedRange),
+enum MapResult<N = u64> {
+    Mapped(SeedRange<N>),
+    Unmapped(SeedRange<N>),
 }
 
-struct Map {
-    to: String,
-    ranges: Vec<MapRange>,
+// `ranges` is kept sorted by source so lookups can binary search, which is
+// only sound because `new` rejects maps whose source intervals overlap.
+#[derive(Debug)]
+struct Map<N = u64> {
+    to: CategoryId,
+    ranges: Vec<MapRange<N>>,
+    // 1-based input line of each entry in `ranges`
+    range_lines: Vec<usize>,
+    // position of each entry in `ranges` within the map as written
+    range_indices: Vec<usize>,
+    // `ranges[i].source`, searched with `partition_point`
+    sources: Vec<N>,
 }
-struct Almanac {
-    seeds: Vec<u64>,
-    maps: HashMap<String, Map>,
-}
-impl Almanac {
-    fn get_locations(&self) -> Vec<u64> {
-        let mut locations: Vec<u64> = Vec::new();
-        for seed in &self.seeds {
-            let mut current = *seed;
-            let mut next_map = "seed";
-            while let Some(map) = self.maps.get(next_map) {
-                for range in &map.ranges {
-                    if let Some(converted) = range.convert(current) {
-                        current = converted;
-                        break;
-                    }
-                }
-                next_map = &map.to;
+impl<N: Unsigned> Map<N> {
+    fn new(
+        to: CategoryId,
+        ranges: Vec<MapRange<N>>,
+        range_lines: Vec<usize>,
+    ) -> Result<Self, AlmanacError<N>> {
+        let mut entries: Vec<(MapRange<N>, usize, usize)> = ranges
+            .into_iter()
+            .zip(range_lines)
+            .enumerate()
+            .map(|(idx, (range, line))| (range, line, idx))
+            .collect();
+        entries.sort_by_key(|(range, _, _)| range.source);
+        // sorted by source, any overlap shows up between neighbours
+        for pair in entries.windows(2) {
+            let [(range, line, _), (next, next_line, _)] = pair else {
+                unreachable!()
+            };
+            if next.source <= range.last_source() {
+                return Err(AlmanacError::OverlappingRanges {
+                    line: *line.min(next_line),
+                    other: *line.max(next_line),
+                });
             }
-            locations.push(current);
         }
-        locations
+        let mut ranges = Vec::with_capacity(entries.len());
+        let mut range_lines = Vec::with_capacity(entries.len());
+        let mut range_indices = Vec::with_capacity(entries.len());
+        for (range, line, idx) in entries {
+            ranges.push(range);
+            range_lines.push(line);
+            range_indices.push(idx);
+        }
+        let sources = ranges.iter().map(|range| range.source).collect();
+        Ok(Map {
+            to,
+            ranges,
+            range_lines,
+            range_indices,
+            sources,
+        })
     }
 
-    fn get_location_ranges(&self) -> Vec<SeedRange> {
-        let mut seed_ranges: HashSet<_> = self
-            .seeds
-            .chunks_exact(2)
-            .map(|c| SeedRange {
-                start: c[0],
-                length: c[1],
-            })
+    fn find(&self, value: N) -> Option<(usize, N)> {
+        let idx = self.sources.partition_point(|source| *source <= value);
+        let idx = idx.checked_sub(1)?;
+        self.ranges[idx]
+            .convert(value)
+            .map(|converted| (idx, converted))
+    }
+
+    // works on inclusive ends throughout, since the exclusive end of a range
+    // finishing at `N::MAX` is not representable
+    fn split_range(&self, seed_range: SeedRange<N>) -> Vec<(SeedRange<N>, Option<usize>)> {
+        let mut splits = Vec::new();
+        let last = seed_range.last();
+        let mut cursor = seed_range.start;
+
+        // first range that could contain the start of seed_range
+        let first = self
+            .sources
+            .partition_point(|source| *source <= cursor)
+            .saturating_sub(1);
+        for (idx, map_range) in self.ranges.iter().enumerate().skip(first) {
+            if map_range.source > last {
+                break;
+            }
+            let range_last = map_range.last_source();
+            if range_last < cursor {
+                continue;
+            }
+            if cursor < map_range.source {
+                splits.push((
+                    SeedRange {
+                        start: cursor,
+                        length: map_range.source - cursor,
+                    },
+                    None,
+                ));
+                cursor = map_range.source;
+            }
+            let overlap_last = range_last.min(last);
+            splits.push((
+                SeedRange {
+                    start: map_range.dest + (cursor - map_range.source),
+                    length: overlap_last - cursor + N::ONE,
+                },
+                Some(idx),
+            ));
+            if overlap_last == last {
+                return splits;
+            }
+            cursor = overlap_last + N::ONE;
+        }
+        splits.push((
+            SeedRange {
+                start: cursor,
+                length: last - cursor + N::ONE,
+            },
+            None,
+        ));
+        splits
+    }
+}
+#[derive(Debug)]
+struct Almanac<N = u64> {
+    seeds: SeedSpec<N>,
+    categories: Categories,
+    // indexed by the id of the category each map converts from
+    maps: Vec<Option<Map<N>>>,
+}
+impl<N: Unsigned> Almanac<N> {
+    fn new(seeds: SeedSpec<N>) -> Self {
+        Almanac {
+            seeds,
+            categories: Categories::default(),
+            maps: Vec::new(),
+        }
+    }
+
+    // a second map from the same category replaces the first
+    fn insert_map(
+        &mut self,
+        from: &str,
+        to: &str,
+        ranges: Vec<MapRange<N>>,
+        range_lines: Vec<usize>,
+    ) -> Result<(), AlmanacError<N>> {
+        let from = self.categories.intern(from);
+        let to = self.categories.intern(to);
+        let map = Map::new(to, ranges, range_lines)?;
+        self.maps.resize_with(self.categories.len(), || None);
+        self.maps[from.index()] = Some(map);
+        Ok(())
+    }
+
+    fn name(&self, id: CategoryId) -> &str {
+        self.categories.name(id)
+    }
+
+    fn map_from(&self, from: CategoryId) -> Option<&Map<N>> {
+        self.maps.get(from.index())?.as_ref()
+    }
+
+    fn maps(&self) -> impl Iterator<Item = (CategoryId, &Map<N>)> {
+        self.categories
+            .ids()
+            .filter_map(|from| self.map_from(from).map(|map| (from, map)))
+    }
+
+    // the maps reached by following `to` from "seed"
+    fn chain(&self) -> impl Iterator<Item = (CategoryId, &Map<N>)> {
+        let start = self
+            .categories
+            .get("seed")
+            .and_then(|seed| self.map_from(seed).map(|map| (seed, map)));
+        std::iter::successors(start, |(_, map)| {
+            self.map_from(map.to).map(|next| (map.to, next))
+        })
+    }
+
+    // the chain starting at "seed" first, then any other maps by name
+    fn ordered_maps(&self) -> Vec<(CategoryId, &Map<N>)> {
+        let mut ordered: Vec<(CategoryId, &Map<N>)> = Vec::new();
+        for (from, map) in self.chain() {
+            if ordered.iter().any(|(seen, _)| *seen == from) {
+                break;
+            }
+            ordered.push((from, map));
+        }
+
+        let mut rest: Vec<(CategoryId, &Map<N>)> = self
+            .maps()
+            .filter(|(from, _)| !ordered.iter().any(|(seen, _)| seen == from))
             .collect();
+        rest.sort_by_key(|(from, _)| self.name(*from));
+        ordered.extend(rest);
+        ordered
+    }
 
-        let mut next_map = "seed";
+    fn get_locations(&self) -> Vec<N> {
+        self.seeds
+            .seeds()
+            .map(|seed| self.get_location(seed))
+            .collect()
+    }
 
-        while let Some(map) = self.maps.get(next_map) {
-            let mut tmp_seed_ranges = HashSet::new();
+    fn get_location(&self, seed: N) -> N {
+        let mut current = seed;
+        for (_, map) in self.chain() {
+            if let Some((_, converted)) = map.find(current) {
+                current = converted;
+            }
+        }
+        current
+    }
+
+    fn get_location_ranges(&self) -> Vec<SeedRange<N>> {
+        self.get_location_ranges_with_stats().0
+    }
+
+    fn get_location_ranges_with_stats(&self) -> (Vec<SeedRange<N>>, Vec<StageStats>) {
+        let mut seed_ranges = normalise_ranges(self.seeds.ranges());
+
+        let mut stats = Vec::new();
+
+        for (from, map) in self.chain() {
+            let mut tmp_seed_ranges = Vec::new();
 
             for sr in seed_ranges {
-                let mut unmapped_set = VecDeque::new();
-                unmapped_set.push_front(sr);
-
-                while let Some(seed_range) = unmapped_set.pop_back() {
-                    let mut mapped_set = HashSet::new();
-
-                    for map_range in &map.ranges {
-                        let map_results = map_range.map_range(&seed_range);
-                        for map_result in map_results {
-                            match map_result {
-                                MapResult::Mapped(mapped) => {
-                                    mapped_set.insert(mapped);
-                                }
-                                MapResult::Unmapped(unmapped) => {
-                                    if unmapped != seed_range {
-                                        unmapped_set.push_back(unmapped)
-                                    }
-                                }
-                            }
-                        }
-                    }
+                for (split, _) in map.split_range(sr) {
+                    tmp_seed_ranges.push(split);
+                }
+            }
+            let before = tmp_seed_ranges.len();
+            seed_ranges = normalise_ranges(tmp_seed_ranges);
+            stats.push(StageStats {
+                from: self.name(from).to_string(),
+                to: self.name(map.to).to_string(),
+                before_merge: before,
+                after_merge: seed_ranges.len(),
+            });
+        }
+
+        (seed_ranges, stats)
+    }
+}
 
-                    if mapped_set.is_empty() {
-                        // this seed range has no mappings
-                        tmp_seed_ranges.insert(seed_range);
-                    } else {
-                        for mapped in mapped_set {
-                            tmp_seed_ranges.insert(mapped);
-                        }
+#[derive(Debug, PartialEq)]
+struct StageStats {
+    from: String,
+    to: String,
+    before_merge: usize,
+    after_merge: usize,
+}
+
+// sorts by start and coalesces overlapping or touching ranges; a union
+// covering every value has no representable length, so it is kept as
+// `0..=MAX-1` followed by `MAX..=MAX`
+fn normalise_ranges<N: Unsigned>(
+    ranges: impl IntoIterator<Item = SeedRange<N>>,
+) -> Vec<SeedRange<N>> {
+    let mut sorted: Vec<SeedRange<N>> = ranges
+        .into_iter()
+        .filter(|sr| sr.length > N::ZERO)
+        .collect();
+    sorted.sort_by_key(|sr| sr.start);
+
+    let mut merged: Vec<SeedRange<N>> = Vec::with_capacity(sorted.len());
+    for sr in sorted {
+        match merged.last_mut() {
+            Some(prev)
+                if prev
+                    .last()
+                    .checked_add(N::ONE)
+                    .is_none_or(|next| sr.start <= next) =>
+            {
+                let last = prev.last().max(sr.last());
+                match (last - prev.start).checked_add(N::ONE) {
+                    Some(length) => prev.length = length,
+                    None => {
+                        prev.length = N::MAX;
+                        merged.push(SeedRange {
+                            start: N::MAX,
+                            length: N::ONE,
+                        });
                     }
                 }
             }
-            seed_ranges = tmp_seed_ranges;
-            next_map = &map.to;
+            _ => merged.push(sr),
         }
+    }
+    merged
+}
 
-        let mut sorted: Vec<_> = seed_ranges.into_iter().collect();
-        sorted.sort_by_key(|sr| sr.start);
-        sorted
+impl<N: Unsigned> PartialEq for Almanac<N> {
+    // categories are compared by name since ids depend on input order, and
+    // line numbers only record where a range came from
+    fn eq(&self, other: &Self) -> bool {
+        let same_map = |from: CategoryId, map: &Map<N>| {
+            other
+                .categories
+                .get(self.name(from))
+                .and_then(|other_from| other.map_from(other_from))
+                .is_some_and(|other_map| {
+                    other.name(other_map.to) == self.name(map.to) && other_map.ranges == map.ranges
+                })
+        };
+        self.seeds == other.seeds
+            && self.maps().count() == other.maps().count()
+            && self.maps().all(|(from, map)| same_map(from, map))
+    }
+}
+
+impl<N: Unsigned> fmt::Display for Almanac<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "seeds:")?;
+        for seed in self.seeds.values() {
+            write!(f, " {seed}")?;
+        }
+        writeln!(f)?;
+        for (from, map) in self.ordered_maps() {
+            writeln!(f)?;
+            writeln!(f, "{}-to-{} map:", self.name(from), self.name(map.to))?;
+            for range in &map.ranges {
+                writeln!(f, "{} {} {}", range.dest, range.source, range.length)?;
+            }
+        }
+        Ok(())
     }
 }
 
+// never empty once built by the parser or `normalise_ranges`
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-struct SeedRange {
-    start: u64,
-    length: u64,
+struct SeedRange<N = u64> {
+    start: N,
+    length: N,
 }
-impl SeedRange {
-    fn last(&self) -> u64 {
-        self.start + self.length - 1
+impl<N: Unsigned> SeedRange<N> {
+    fn last(&self) -> N {
+        debug_assert!(self.length > N::ZERO, "empty seed range at {}", self.start);
+        self.start + (self.length - N::ONE)
     }
 }
 
@@ -301,7 +684,7 @@ mod tests {
 
     use super::*;
 
-    const EXAMPLE_INPUT: &str = "seeds: 79 14 55 13
+    pub(super) const EXAMPLE_INPUT: &str = "seeds: 79 14 55 13
 
 seed-to-soil map:
 50 98 2
@@ -347,6 +730,315 @@ humidity-to-location map:
         assert_eq!(lowest_location, 46);
     }
 
+    #[test]
+    fn test_seed_interpretations() {
+        let inclusive = EXAMPLE_INPUT.replacen("79 14 55 13", "79 92 55 67", 1);
+        assert_eq!(
+            lowest_location::<u64>(&inclusive, SeedKind::InclusiveStartEnd),
+            Ok(46)
+        );
+
+        let odd = EXAMPLE_INPUT.replacen("79 14 55 13", "79 14 55", 1);
+        assert_eq!(lowest_location::<u64>(&odd, SeedKind::Individual), Ok(43));
+        assert_eq!(
+            lowest_location::<u64>(&odd, SeedKind::StartLength),
+            Err(AlmanacError::Seeds(SeedError::OddPairList(3)))
+        );
+
+        let no_seeds = EXAMPLE_INPUT.replacen("79 14 55 13", "", 1);
+        assert_eq!(
+            lowest_location::<u64>(&no_seeds, SeedKind::Individual),
+            Err(AlmanacError::NoSeeds)
+        );
+    }
+
+    #[test]
+    fn test_wide_backend_agrees() {
+        for kind in [SeedKind::Individual, SeedKind::StartLength] {
+            assert_eq!(
+                lowest_location::<u128>(EXAMPLE_INPUT, kind).unwrap(),
+                u128::from(lowest_location::<u64>(EXAMPLE_INPUT, kind).unwrap())
+            );
+        }
+        assert_eq!(
+            lowest_location::<u128>(INPUT, SeedKind::StartLength).unwrap(),
+            u128::from(part_two(INPUT))
+        );
+    }
+
+    #[test]
+    fn test_ranges_ending_at_max() {
+        // seeds u64::MAX-2..=u64::MAX, the last two mapped to 5..=6
+        let input =
+            "seeds: 18446744073709551613 3\n\nseed-to-location map:\n5 18446744073709551614 2\n";
+
+        assert_eq!(lowest_location::<u64>(input, SeedKind::Individual), Ok(3));
+        assert_eq!(lowest_location::<u64>(input, SeedKind::StartLength), Ok(5));
+        let almanac = parse_input(input, SeedKind::StartLength).unwrap();
+        assert_eq!(almanac.get_locations(), vec![u64::MAX - 2, 5, 6]);
+
+        let everything = "seeds: 0 18446744073709551615\n";
+        assert_eq!(
+            lowest_location::<u64>(everything, SeedKind::InclusiveStartEnd),
+            Err(AlmanacError::Seeds(SeedError::Overflow {
+                start: 0,
+                length: u64::MAX
+            }))
+        );
+        assert_eq!(
+            lowest_location::<u128>(everything, SeedKind::InclusiveStartEnd),
+            Ok(0)
+        );
+    }
+
+    #[test]
+    fn test_invalid_ranges() {
+        let with_map = |range: &str| format!("seeds: 1 2\n\nseed-to-soil map:\n0 0 1\n{range}\n");
+        let parse = |input: &str| parse_input(input, SeedKind::StartLength).unwrap_err();
+
+        assert_eq!(
+            parse(&with_map("5 18446744073709551615 2")),
+            AlmanacError::RangeOverflow { line: 5 }
+        );
+        assert_eq!(
+            parse(&with_map("18446744073709551615 5 2")),
+            AlmanacError::RangeOverflow { line: 5 }
+        );
+        assert_eq!(
+            parse(&with_map("1 2 0")),
+            AlmanacError::EmptyRange { line: 5 }
+        );
+        assert_eq!(
+            parse(&with_map("1 2")),
+            AlmanacError::MalformedRange { line: 5, values: 2 }
+        );
+        assert_eq!(
+            parse(&with_map("18446744073709551616 1 1")),
+            AlmanacError::InvalidNumber {
+                line: 5,
+                token: "18446744073709551616".to_string()
+            }
+        );
+        assert_eq!(
+            parse("seeds: 1 x\n"),
+            AlmanacError::InvalidNumber {
+                line: 1,
+                token: "x".to_string()
+            }
+        );
+        assert_eq!(
+            parse(&with_map("5 0 1")),
+            AlmanacError::OverlappingRanges { line: 4, other: 5 }
+        );
+        assert_eq!(parse(""), AlmanacError::EmptyInput);
+        assert_eq!(
+            parse("79 14 55 13\n"),
+            AlmanacError::MissingSeedsLabel { line: 1 }
+        );
+        assert_eq!(
+            parse("seeds: 1 2\n\nseed-soil map:\n0 0 1\n"),
+            AlmanacError::MalformedHeader {
+                line: 3,
+                header: "seed-soil map:".to_string()
+            }
+        );
+        // a range line straight after the seeds is read as a header
+        assert_eq!(
+            parse("seeds: 1 2\n\n0 0 1\n"),
+            AlmanacError::MalformedHeader {
+                line: 3,
+                header: "0 0 1".to_string()
+            }
+        );
+        assert_eq!(
+            parse("seeds: 1 2\n\n-to-soil map:\n0 0 1\n"),
+            AlmanacError::MalformedHeader {
+                line: 3,
+                header: "-to-soil map:".to_string()
+            }
+        );
+        assert!(parse_almanac::<u128>(
+            &with_map("18446744073709551616 1 1"),
+            SeedKind::StartLength
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_overlapping_ranges() {
+        let with_map = |ranges: &str| format!("seeds: 1 2\n\nseed-to-soil map:\n{ranges}\n");
+        let parse = |input: &str| parse_input(input, SeedKind::StartLength);
+
+        // touching ranges are fine, sharing a single value is not
+        assert!(parse(&with_map("0 10 5\n50 15 5")).is_ok());
+        assert_eq!(
+            parse(&with_map("0 10 5\n50 14 5")).unwrap_err(),
+            AlmanacError::OverlappingRanges { line: 4, other: 5 }
+        );
+        // reported in input order whatever the sorted order
+        assert_eq!(
+            parse(&with_map("50 20 5\n0 10 5\n9 0 3\n30 12 2")).unwrap_err(),
+            AlmanacError::OverlappingRanges { line: 5, other: 7 }
+        );
+        // one range inside another
+        assert_eq!(
+            parse(&with_map("0 10 20\n50 12 2")).unwrap_err(),
+            AlmanacError::OverlappingRanges { line: 4, other: 5 }
+        );
+    }
+
+    #[test]
+    fn test_brute_force_locations_agree_with_ranges() {
+        let almanac = parse_input(EXAMPLE_INPUT, SeedKind::StartLength).unwrap();
+        let mut brute_force = almanac.get_locations();
+        brute_force.sort();
+        brute_force.dedup();
+        let from_ranges: Vec<u64> = almanac
+            .get_location_ranges()
+            .iter()
+            .flat_map(|sr| sr.start..=sr.last())
+            .collect();
+
+        assert_eq!(brute_force, from_ranges);
+        assert_eq!(brute_force.first(), Some(&46));
+    }
+
+    #[test]
+    fn test_format_round_trip_example() {
+        let almanac = parse_input(EXAMPLE_INPUT, SeedKind::StartLength).unwrap();
+        let formatted = almanac.to_string();
+
+        assert!(
+            formatted.starts_with("seeds: 79 14 55 13\n\nseed-to-soil map:\n52 50 48\n50 98 2\n")
+        );
+        assert!(formatted.ends_with("humidity-to-location map:\n60 56 37\n56 93 4\n"));
+        assert_eq!(
+            parse_input(&formatted, SeedKind::StartLength).unwrap(),
+            almanac
+        );
+        assert_eq!(
+            parse_input(&formatted, SeedKind::StartLength)
+                .unwrap()
+                .to_string(),
+            formatted
+        );
+    }
+
+    #[test]
+    fn test_format_round_trip_input() {
+        let almanac = parse_input(INPUT, SeedKind::StartLength).unwrap();
+        let formatted = almanac.to_string();
+
+        assert_eq!(
+            parse_input(&formatted, SeedKind::StartLength).unwrap(),
+            almanac
+        );
+        assert_eq!(
+            parse_input(&formatted, SeedKind::StartLength)
+                .unwrap()
+                .to_string(),
+            formatted
+        );
+    }
+
+    #[test]
+    fn test_format_normalises_whitespace() {
+        let messy =
+            "seeds:   79 14\t55 13  \n   \nseed-to-soil   map:\n  52 50   48\n50 98 2\n\n\n";
+        let formatted = parse_input(messy, SeedKind::StartLength)
+            .unwrap()
+            .to_string();
+
+        assert_eq!(
+            formatted,
+            "seeds: 79 14 55 13\n\nseed-to-soil map:\n52 50 48\n50 98 2\n"
+        );
+    }
+
+    #[test]
+    fn test_normalise_ranges() {
+        let ranges: Vec<SeedRange> = vec![
+            SeedRange {
+                start: 20,
+                length: 5,
+            },
+            SeedRange {
+                start: 1,
+                length: 4,
+            },
+            SeedRange {
+                start: 5,
+                length: 3,
+            },
+            SeedRange {
+                start: 22,
+                length: 10,
+            },
+            SeedRange {
+                start: 40,
+                length: 0,
+            },
+            SeedRange {
+                start: 2,
+                length: 1,
+            },
+        ];
+        let expected = vec![
+            SeedRange {
+                start: 1,
+                length: 7,
+            },
+            SeedRange {
+                start: 20,
+                length: 12,
+            },
+        ];
+
+        assert_eq!(normalise_ranges(ranges), expected);
+
+        // the whole domain has no representable length
+        let everything = normalise_ranges([
+            SeedRange {
+                start: 5,
+                length: u64::MAX - 4,
+            },
+            SeedRange {
+                start: 0,
+                length: 10,
+            },
+        ]);
+        assert_eq!(
+            everything,
+            vec![
+                SeedRange {
+                    start: 0,
+                    length: u64::MAX
+                },
+                SeedRange {
+                    start: u64::MAX,
+                    length: 1
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stage_stats() {
+        let almanac = parse_input(EXAMPLE_INPUT, SeedKind::StartLength).unwrap();
+        let (location_ranges, stats) = almanac.get_location_ranges_with_stats();
+
+        assert_eq!(stats.len(), 7);
+        assert_eq!(stats[0].from, "seed");
+        assert_eq!(stats[6].to, "location");
+        for stage in &stats {
+            assert!(stage.after_merge <= stage.before_merge);
+        }
+        assert_eq!(stats[6].after_merge, location_ranges.len());
+        assert!(location_ranges
+            .windows(2)
+            .all(|w| w[0].last() + 1 < w[1].start));
+    }
+
     #[test]
     fn test_range_is_out_before() {
         /*
@@ -354,7 +1046,7 @@ humidity-to-location map:
         MAP:            |-----|
         EXP:  |------|
          */
-        let map_range = MapRange {
+        let map_range: MapRange = MapRange {
             source: 10,
             length: 10,
             dest: 1,
@@ -378,7 +1070,7 @@ humidity-to-location map:
         MAP:        |-----|
         EXP:  |-----||
          */
-        let map_range = MapRange {
+        let map_range: MapRange = MapRange {
             source: 10,
             length: 10,
             dest: 1,
@@ -412,7 +1104,7 @@ humidity-to-location map:
         MAP:        |-----|
         EXP:  |-----|xx|
          */
-        let map_range = MapRange {
+        let map_range: MapRange = MapRange {
             source: 10,
             length: 10,
             dest: 1,
@@ -446,7 +1138,7 @@ humidity-to-location map:
         MAP:        |-----|
         EXP:  |-----|xxxxx|
          */
-        let map_range = MapRange {
+        let map_range: MapRange = MapRange {
             source: 10,
             length: 10,
             dest: 1,
@@ -480,7 +1172,7 @@ humidity-to-location map:
         MAP:        |-----|
         EXP:  |-----|xxxxx|| (3 results)
          */
-        let map_range = MapRange {
+        let map_range: MapRange = MapRange {
             source: 10,
             length: 10,
             dest: 1,
@@ -518,7 +1210,7 @@ humidity-to-location map:
         MAP:        |-----|
         EXP:  |-----|xxxxx|---|
          */
-        let map_range = MapRange {
+        let map_range: MapRange = MapRange {
             source: 10,
             length: 10,
             dest: 1,
@@ -556,7 +1248,7 @@ humidity-to-location map:
         MAP:        |-----|
         EXP:        |xxxxx|
          */
-        let map_range = MapRange {
+        let map_range: MapRange = MapRange {
             source: 10,
             length: 10,
             dest: 1,
@@ -584,7 +1276,7 @@ humidity-to-location map:
         MAP:        |-----|
         EXP:        |xxxx|
          */
-        let map_range = MapRange {
+        let map_range: MapRange = MapRange {
             source: 10,
             length: 10,
             dest: 1,
@@ -612,7 +1304,7 @@ humidity-to-location map:
         MAP:        |-----|
         EXP:         |xxx|
          */
-        let map_range = MapRange {
+        let map_range: MapRange = MapRange {
             source: 10,
             length: 10,
             dest: 1,
@@ -640,7 +1332,7 @@ humidity-to-location map:
         MAP:        |-----|
         EXP:          |xxx|
          */
-        let map_range = MapRange {
+        let map_range: MapRange = MapRange {
             source: 10,
             length: 10,
             dest: 1,
@@ -668,7 +1360,7 @@ humidity-to-location map:
         MAP:        |-----|
         EXP:          |xxx|| (2 results)
          */
-        let map_range = MapRange {
+        let map_range: MapRange = MapRange {
             source: 10,
             length: 10,
             dest: 1,
@@ -702,7 +1394,7 @@ humidity-to-location map:
         MAP:        |-----|
         EXP:          |xxx|---|
          */
-        let map_range = MapRange {
+        let map_range: MapRange = MapRange {
             source: 10,
             length: 10,
             dest: 1,
@@ -736,7 +1428,7 @@ humidity-to-location map:
         MAP:        |-----|
         EXP:             ||------|
          */
-        let map_range = MapRange {
+        let map_range: MapRange = MapRange {
             source: 10,
             length: 10,
             dest: 1,
@@ -770,7 +1462,7 @@ humidity-to-location map:
         MAP:        |-----|
         EXP:               |------|
          */
-        let map_range = MapRange {
+        let map_range: MapRange = MapRange {
             source: 10,
             length: 10,
             dest: 1,
@@ -794,7 +1486,7 @@ humidity-to-location map:
         MAP:        |-----|
         EXP:                        |------|
          */
-        let map_range = MapRange {
+        let map_range: MapRange = MapRange {
             source: 10,
             length: 10,
             dest: 1,
@@ -810,4 +1502,47 @@ humidity-to-location map:
 
         assert_eq!(convert, expected);
     }
+
+    #[test]
+    fn test_range_at_max() {
+        /*
+        SEED: |-----------| MAX
+        MAP:        |-----| MAX
+        EXP:  |-----|xxxxx|
+         */
+        let map_range: MapRange = MapRange {
+            source: u64::MAX - 9,
+            length: 10,
+            dest: 0,
+        };
+
+        let seed_range = SeedRange {
+            start: 0,
+            length: u64::MAX,
+        };
+
+        let expected = vec![
+            MapResult::Unmapped(SeedRange {
+                start: 0,
+                length: u64::MAX - 9,
+            }),
+            MapResult::Mapped(SeedRange {
+                start: 0,
+                length: 9,
+            }),
+        ];
+
+        assert_eq!(map_range.map_range(&seed_range), expected);
+
+        let seed_range = SeedRange {
+            start: u64::MAX,
+            length: 1,
+        };
+        let expected = vec![MapResult::Mapped(SeedRange {
+            start: 9,
+            length: 1,
+        })];
+
+        assert_eq!(map_range.map_range(&seed_range), expected);
+    }
 }
@@ -0,0 +1,52 @@
+use std::{
+    fmt,
+    hash::Hash,
+    num::ParseIntError,
+    ops::{Add, Sub},
+    str::FromStr,
+};
+
+// The unsigned integer the almanac arithmetic runs on. Plain `+` and `-` are
+// only used once a range has been validated not to overflow; anything that
+// could overflow goes through the checked methods.
+pub(super) trait Unsigned:
+    Copy
+    + Ord
+    + Hash
+    + fmt::Debug
+    + fmt::Display
+    + FromStr<Err = ParseIntError>
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Send
+    + Sync
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const MAX: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+}
+
+macro_rules! impl_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl Unsigned for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const MAX: Self = <$t>::MAX;
+
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_add(self, rhs)
+                }
+
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned!(u64, u128);
@@ -0,0 +1,196 @@
+// Randomised cross-check of `Almanac::get_location_ranges` against mapping
+// every seed individually through `MapRange::convert`.
+
+use std::collections::BTreeSet;
+
+use super::{Almanac, MapRange, SeedRange, SeedSpec};
+use crate::rng::Rng;
+
+const BASE_SEED: u64 = 0x0005_eed5;
+const CASES: u64 = 500;
+const DOMAIN: u64 = 64;
+
+#[derive(Debug, Clone)]
+struct Case {
+    maps: Vec<Vec<MapRange>>,
+    seed_ranges: Vec<SeedRange>,
+}
+
+impl Case {
+    fn generate(rng: &mut Rng) -> Case {
+        let map_count = rng.gen_range(1..4);
+        let maps = (0..map_count).map(|_| generate_map(rng)).collect();
+        let seed_ranges = (0..rng.gen_range(1..4))
+            .map(|_| SeedRange {
+                start: rng.gen_range(0..DOMAIN),
+                length: rng.gen_range(1..DOMAIN / 2),
+            })
+            .collect();
+        Case { maps, seed_ranges }
+    }
+
+    fn almanac(&self, seeds: SeedSpec) -> Almanac {
+        let mut almanac = Almanac::new(seeds);
+        for (idx, ranges) in self.maps.iter().enumerate() {
+            let from = category_name(idx, self.maps.len());
+            let to = category_name(idx + 1, self.maps.len());
+            let lines = (1..=ranges.len()).collect();
+            almanac
+                .insert_map(&from, &to, ranges.clone(), lines)
+                .unwrap();
+        }
+        almanac
+    }
+
+    fn check(&self) -> Result<(), String> {
+        let from_ranges: BTreeSet<u64> = self
+            .almanac(SeedSpec::StartLength(self.seed_ranges.clone()))
+            .get_location_ranges()
+            .iter()
+            .flat_map(|sr| sr.start..sr.start + sr.length)
+            .collect();
+
+        let seeds = self
+            .seed_ranges
+            .iter()
+            .flat_map(|sr| sr.start..sr.start + sr.length)
+            .collect();
+        let brute_force: BTreeSet<u64> = self
+            .almanac(SeedSpec::Individual(seeds))
+            .get_locations()
+            .into_iter()
+            .collect();
+
+        if from_ranges == brute_force {
+            Ok(())
+        } else {
+            Err(format!(
+                "ranges gave {from_ranges:?}, brute force gave {brute_force:?}"
+            ))
+        }
+    }
+
+    fn shrink_candidates(&self) -> Vec<Case> {
+        let mut candidates = Vec::new();
+        if self.maps.len() > 1 {
+            for idx in 0..self.maps.len() {
+                let mut case = self.clone();
+                case.maps.remove(idx);
+                candidates.push(case);
+            }
+        }
+        if self.seed_ranges.len() > 1 {
+            for idx in 0..self.seed_ranges.len() {
+                let mut case = self.clone();
+                case.seed_ranges.remove(idx);
+                candidates.push(case);
+            }
+        }
+        for (map_idx, ranges) in self.maps.iter().enumerate() {
+            for (range_idx, range) in ranges.iter().enumerate() {
+                let mut case = self.clone();
+                case.maps[map_idx].remove(range_idx);
+                candidates.push(case);
+
+                if range.length > 1 {
+                    let mut case = self.clone();
+                    case.maps[map_idx][range_idx].length -= 1;
+                    candidates.push(case);
+                }
+            }
+        }
+        for (idx, sr) in self.seed_ranges.iter().enumerate() {
+            if sr.length > 1 {
+                let mut case = self.clone();
+                case.seed_ranges[idx].length -= 1;
+                candidates.push(case.clone());
+                case.seed_ranges[idx].start += 1;
+                candidates.push(case);
+            }
+        }
+        candidates
+    }
+
+    fn shrink(self, fails: impl Fn(&Case) -> bool) -> Case {
+        let mut current = self;
+        while let Some(smaller) = current.shrink_candidates().into_iter().find(&fails) {
+            current = smaller;
+        }
+        current
+    }
+}
+
+fn category_name(idx: usize, map_count: usize) -> String {
+    match idx {
+        0 => "seed".to_string(),
+        _ if idx == map_count => "location".to_string(),
+        _ => format!("category{idx}"),
+    }
+}
+
+// source intervals within one map never overlap, as in the puzzle input
+fn generate_map(rng: &mut Rng) -> Vec<MapRange> {
+    let mut ranges = Vec::new();
+    let mut cursor = 0;
+    while cursor < DOMAIN {
+        let source = cursor + rng.gen_range(0..8);
+        let length = rng.gen_range(1..16);
+        if source + length > DOMAIN {
+            break;
+        }
+        ranges.push(MapRange {
+            source,
+            dest: rng.gen_range(0..DOMAIN),
+            length,
+        });
+        cursor = source + length;
+    }
+    // order within a map should not matter
+    for idx in (1..ranges.len()).rev() {
+        let other = rng.gen_range(0..idx as u64 + 1) as usize;
+        ranges.swap(idx, other);
+    }
+    ranges
+}
+
+#[test]
+fn test_ranges_match_brute_force() {
+    for case_idx in 0..CASES {
+        let seed = BASE_SEED + case_idx;
+        let case = Case::generate(&mut Rng::new(seed));
+        if case.check().is_err() {
+            let minimal = case.shrink(|c| c.check().is_err());
+            panic!(
+                "rng seed {seed}: {}\nminimal case: {minimal:#?}",
+                minimal.check().unwrap_err()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_shrink_reaches_minimal_case() {
+    // a deliberately broken check: any case whose first seed range has
+    // length >= 3 "fails", so shrinking must stop at exactly 3
+    let case = Case {
+        maps: vec![vec![MapRange {
+            source: 0,
+            dest: 10,
+            length: 5,
+        }]],
+        seed_ranges: vec![
+            SeedRange {
+                start: 4,
+                length: 9,
+            },
+            SeedRange {
+                start: 1,
+                length: 2,
+            },
+        ],
+    };
+    let current = case.shrink(|c| c.seed_ranges[0].length >= 3);
+    assert_eq!(current.seed_ranges.len(), 1);
+    assert_eq!(current.seed_ranges[0].length, 3);
+    assert!(current.maps[0].is_empty());
+}
@@ -0,0 +1,317 @@
+use crate::cli::option_value;
+
+use super::{normalise_ranges, parse_input, Almanac, SeedKind, SeedRange};
+
+const DEFAULT_LOWEST: usize = 5;
+
+// a location interval together with the seeds that map onto it, one to one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct Origin {
+    pub(super) location: SeedRange,
+    pub(super) seeds: SeedRange,
+}
+
+// a single location and one seed that lands on it, with the seed
+// sub-range that seed maps from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct SeedLocation {
+    pub(super) location: u64,
+    pub(super) seed: u64,
+    pub(super) seeds: SeedRange,
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) struct LocationReport {
+    pub(super) intervals: Vec<SeedRange>,
+    // u128, as every u64 location together is one more than `u64::MAX`
+    pub(super) covered: u128,
+    // sorted by location; each lies within a single interval
+    pub(super) origins: Vec<Origin>,
+    // distinct locations, smallest first
+    pub(super) lowest: Vec<SeedLocation>,
+}
+impl LocationReport {
+    pub(super) fn origins_within(&self, interval: SeedRange) -> impl Iterator<Item = &Origin> {
+        let first = self
+            .origins
+            .partition_point(|origin| origin.location.start < interval.start);
+        self.origins[first..]
+            .iter()
+            .take_while(move |origin| origin.location.start <= interval.last())
+    }
+}
+
+pub(super) fn run(input: &str, args: &[String]) {
+    let mut lowest = DEFAULT_LOWEST;
+    let mut below = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let parsed = match arg.as_str() {
+            "--lowest" => option_value(&mut args, arg).map(|val| lowest = val),
+            "--below" => option_value(&mut args, arg).map(|val| below = Some(val)),
+            other => Err(format!("unknown report option: {other}")),
+        };
+        if let Err(e) = parsed {
+            eprintln!("{e}");
+            return;
+        }
+    }
+
+    let almanac = match parse_input(input, SeedKind::StartLength) {
+        Ok(almanac) => almanac,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    let report = almanac.location_report(lowest);
+    println!(
+        "{} location intervals covering {} values",
+        report.intervals.len(),
+        report.covered
+    );
+    for interval in &report.intervals {
+        let seeds: Vec<String> = report
+            .origins_within(*interval)
+            .map(|origin| format!("{}..={}", origin.seeds.start, origin.seeds.last()))
+            .collect();
+        println!(
+            "  {}..={} <- seeds {}",
+            interval.start,
+            interval.last(),
+            seeds.join(", ")
+        );
+    }
+    println!("lowest {} locations:", report.lowest.len());
+    for lowest in &report.lowest {
+        println!(
+            "  {} <- seed {} of {}..={}",
+            lowest.location,
+            lowest.seed,
+            lowest.seeds.start,
+            lowest.seeds.last()
+        );
+    }
+    if let Some(bound) = below {
+        let count = seeds_below(&report.origins, bound);
+        println!("seeds landing below {bound}: {count}");
+    }
+}
+
+impl Almanac {
+    // like `get_location_ranges`, but every piece remembers the seed
+    // sub-range it came from, so nothing is merged along the way
+    pub(super) fn location_origins(&self) -> Vec<Origin> {
+        let mut origins: Vec<Origin> = normalise_ranges(self.seeds.ranges())
+            .into_iter()
+            .map(|sr| Origin {
+                location: sr,
+                seeds: sr,
+            })
+            .collect();
+
+        for (_, map) in self.chain() {
+            let mut next = Vec::new();
+            for origin in origins {
+                // `split_range` yields pieces in input order, so the seed
+                // offset advances by each piece's length
+                let mut offset = 0;
+                for (piece, _) in map.split_range(origin.location) {
+                    next.push(Origin {
+                        location: piece,
+                        seeds: SeedRange {
+                            start: origin.seeds.start + offset,
+                            length: piece.length,
+                        },
+                    });
+                    offset += piece.length;
+                }
+            }
+            origins = next;
+        }
+
+        origins.sort_by_key(|origin| (origin.location.start, origin.seeds.start));
+        origins
+    }
+
+    pub(super) fn location_report(&self, lowest: usize) -> LocationReport {
+        let intervals = self.get_location_ranges();
+        let covered = intervals.iter().map(|sr| u128::from(sr.length)).sum();
+        let origins = self.location_origins();
+        let lowest = lowest_locations(&origins, lowest);
+        LocationReport {
+            intervals,
+            covered,
+            origins,
+            lowest,
+        }
+    }
+}
+
+// the `count` smallest distinct locations, each with the lowest seed among
+// the origins that reach it; origins may overlap in location, so each value
+// is the smallest one still available from any origin
+fn lowest_locations(origins: &[Origin], count: usize) -> Vec<SeedLocation> {
+    let mut lowest = Vec::new();
+    let mut cursor = 0;
+    while lowest.len() < count {
+        let next = origins
+            .iter()
+            .filter(|origin| origin.location.last() >= cursor)
+            .map(|origin| {
+                let location = origin.location.start.max(cursor);
+                let seed = origin.seeds.start + (location - origin.location.start);
+                SeedLocation {
+                    location,
+                    seed,
+                    seeds: origin.seeds,
+                }
+            })
+            .min_by_key(|found| (found.location, found.seed));
+        let Some(next) = next else {
+            break;
+        };
+        lowest.push(next);
+        match next.location.checked_add(1) {
+            Some(after) => cursor = after,
+            None => break,
+        }
+    }
+    lowest
+}
+
+// seeds (not distinct locations) whose location is strictly below `bound`
+pub(super) fn seeds_below(origins: &[Origin], bound: u64) -> u64 {
+    origins
+        .iter()
+        .map(|origin| {
+            bound
+                .saturating_sub(origin.location.start)
+                .min(origin.location.length)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day05::tests::EXAMPLE_INPUT;
+
+    #[test]
+    fn test_location_report() {
+        let almanac = parse_input(EXAMPLE_INPUT, SeedKind::StartLength).unwrap();
+        let report = almanac.location_report(2);
+
+        assert_eq!(report.intervals, almanac.get_location_ranges());
+        assert_eq!(report.intervals[0].start, 46);
+        assert_eq!(report.covered, 27);
+        // seed 82 is the puzzle's answer for location 46
+        let lowest: Vec<(u64, u64)> = report.lowest.iter().map(|l| (l.location, l.seed)).collect();
+        assert_eq!(lowest, vec![(46, 82), (47, 83)]);
+        for lowest in &report.lowest {
+            assert!(lowest.seeds.start <= lowest.seed && lowest.seed <= lowest.seeds.last());
+        }
+        assert_eq!(report.origins, almanac.location_origins());
+    }
+
+    #[test]
+    fn test_origins_within_intervals() {
+        let almanac = parse_input(EXAMPLE_INPUT, SeedKind::StartLength).unwrap();
+        let report = almanac.location_report(0);
+
+        let mut found = Vec::new();
+        for interval in &report.intervals {
+            for origin in report.origins_within(*interval) {
+                assert!(interval.start <= origin.location.start);
+                assert!(origin.location.last() <= interval.last());
+                found.push(*origin);
+            }
+        }
+        // every origin belongs to exactly one interval
+        assert_eq!(found, report.origins);
+    }
+
+    #[test]
+    fn test_lowest_locations_agree_with_brute_force() {
+        let almanac = parse_input(EXAMPLE_INPUT, SeedKind::StartLength).unwrap();
+        let mut brute_force: Vec<(u64, u64)> = almanac
+            .seeds
+            .seeds()
+            .map(|seed| (almanac.get_location(seed), seed))
+            .collect();
+        brute_force.sort();
+        brute_force.dedup_by_key(|(location, _)| *location);
+
+        let lowest = lowest_locations(&almanac.location_origins(), 100);
+        let lowest: Vec<(u64, u64)> = lowest.iter().map(|l| (l.location, l.seed)).collect();
+        assert_eq!(lowest, brute_force);
+    }
+
+    #[test]
+    fn test_lowest_locations_across_overlapping_origins() {
+        let origin = |location: u64, seeds: u64, length: u64| Origin {
+            location: SeedRange {
+                start: location,
+                length,
+            },
+            seeds: SeedRange {
+                start: seeds,
+                length,
+            },
+        };
+        let origins = [origin(10, 100, 3), origin(11, 5, 1), origin(20, 0, 2)];
+        let lowest: Vec<(u64, u64)> = lowest_locations(&origins, 5)
+            .iter()
+            .map(|l| (l.location, l.seed))
+            .collect();
+
+        assert_eq!(
+            lowest,
+            vec![(10, 100), (11, 5), (12, 102), (20, 0), (21, 1)]
+        );
+    }
+
+    #[test]
+    fn test_location_report_whole_domain() {
+        let almanac = parse_input(
+            "seeds: 0 18446744073709551615 18446744073709551615 1\n",
+            SeedKind::StartLength,
+        )
+        .unwrap();
+        let report = almanac.location_report(1);
+
+        assert_eq!(report.intervals.len(), 2);
+        assert_eq!(report.covered, 1 << 64);
+    }
+
+    #[test]
+    fn test_origins_agree_with_brute_force() {
+        let almanac = parse_input(EXAMPLE_INPUT, SeedKind::StartLength).unwrap();
+        let origins = almanac.location_origins();
+
+        assert_eq!(origins.iter().map(|o| o.seeds.length).sum::<u64>(), 27);
+        for origin in &origins {
+            for offset in 0..origin.seeds.length {
+                assert_eq!(
+                    almanac.get_location(origin.seeds.start + offset),
+                    origin.location.start + offset
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_seeds_below() {
+        let almanac = parse_input(EXAMPLE_INPUT, SeedKind::StartLength).unwrap();
+        let origins = almanac.location_origins();
+
+        for bound in [0, 46, 47, 60, 100] {
+            let expected = almanac
+                .seeds
+                .seeds()
+                .filter(|seed| almanac.get_location(*seed) < bound)
+                .count() as u64;
+            assert_eq!(seeds_below(&origins, bound), expected);
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use std::fmt;
+
+use super::{SeedRange, Unsigned};
+
+// how the numbers on the "seeds:" line are to be read
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum SeedKind {
+    Individual,
+    StartLength,
+    InclusiveStartEnd,
+}
+impl SeedKind {
+    pub(super) fn parse(name: &str) -> Option<SeedKind> {
+        match name {
+            "individual" => Some(SeedKind::Individual),
+            "pairs" => Some(SeedKind::StartLength),
+            "inclusive" => Some(SeedKind::InclusiveStartEnd),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum SeedSpec<N = u64> {
+    Individual(Vec<N>),
+    StartLength(Vec<SeedRange<N>>),
+    InclusiveStartEnd(Vec<SeedRange<N>>),
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) enum SeedError<N = u64> {
+    OddPairList(usize),
+    EmptyRange { start: N },
+    Overflow { start: N, length: N },
+    EndBeforeStart { start: N, end: N },
+}
+impl<N: fmt::Display> fmt::Display for SeedError<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeedError::OddPairList(count) => {
+                write!(f, "seed pairs need an even number of values, got {count}")
+            }
+            SeedError::EmptyRange { start } => write!(f, "seed range at {start} is empty"),
+            SeedError::Overflow { start, length } => {
+                write!(f, "seed range {start} {length} runs past the largest value")
+            }
+            SeedError::EndBeforeStart { start, end } => {
+                write!(f, "seed range {start}..={end} ends before it starts")
+            }
+        }
+    }
+}
+
+impl<N: Unsigned> SeedSpec<N> {
+    pub(super) fn new(values: Vec<N>, kind: SeedKind) -> Result<SeedSpec<N>, SeedError<N>> {
+        if kind == SeedKind::Individual {
+            return Ok(SeedSpec::Individual(values));
+        }
+        if !values.len().is_multiple_of(2) {
+            return Err(SeedError::OddPairList(values.len()));
+        }
+
+        let mut ranges = Vec::new();
+        for c in values.chunks_exact(2) {
+            let (start, second) = (c[0], c[1]);
+            let length = match kind {
+                SeedKind::InclusiveStartEnd => second
+                    .checked_sub(start)
+                    .ok_or(SeedError::EndBeforeStart { start, end: second })?
+                    .checked_add(N::ONE)
+                    .ok_or(SeedError::Overflow {
+                        start,
+                        length: N::MAX,
+                    })?,
+                _ => second,
+            };
+            if length == N::ZERO {
+                return Err(SeedError::EmptyRange { start });
+            }
+            // the last seed must itself be representable
+            if start.checked_add(length - N::ONE).is_none() {
+                return Err(SeedError::Overflow { start, length });
+            }
+            ranges.push(SeedRange { start, length });
+        }
+
+        Ok(match kind {
+            SeedKind::InclusiveStartEnd => SeedSpec::InclusiveStartEnd(ranges),
+            _ => SeedSpec::StartLength(ranges),
+        })
+    }
+
+    // the numbers as written on the "seeds:" line
+    pub(super) fn values(&self) -> Vec<N> {
+        match self {
+            SeedSpec::Individual(seeds) => seeds.clone(),
+            SeedSpec::StartLength(ranges) => {
+                ranges.iter().flat_map(|sr| [sr.start, sr.length]).collect()
+            }
+            SeedSpec::InclusiveStartEnd(ranges) => {
+                ranges.iter().flat_map(|sr| [sr.start, sr.last()]).collect()
+            }
+        }
+    }
+
+    pub(super) fn ranges(&self) -> Vec<SeedRange<N>> {
+        match self {
+            SeedSpec::Individual(seeds) => seeds
+                .iter()
+                .map(|seed| SeedRange {
+                    start: *seed,
+                    length: N::ONE,
+                })
+                .collect(),
+            SeedSpec::StartLength(ranges) | SeedSpec::InclusiveStartEnd(ranges) => ranges.clone(),
+        }
+    }
+
+    // every seed described, in order; ranges are expanded one by one and
+    // stop at their last seed, so a range ending at `N::MAX` does not overflow
+    pub(super) fn seeds(&self) -> Box<dyn Iterator<Item = N> + '_> {
+        match self {
+            SeedSpec::Individual(seeds) => Box::new(seeds.iter().copied()),
+            SeedSpec::StartLength(ranges) | SeedSpec::InclusiveStartEnd(ranges) => {
+                Box::new(ranges.iter().flat_map(|sr| {
+                    let last = sr.last();
+                    std::iter::successors(Some(sr.start), move |seed| {
+                        (*seed < last).then(|| *seed + N::ONE)
+                    })
+                }))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seed_spec_kinds() {
+        let values: Vec<u64> = vec![79, 14, 55, 13];
+
+        let individual = SeedSpec::new(values.clone(), SeedKind::Individual).unwrap();
+        assert_eq!(individual.seeds().count(), 4);
+        assert_eq!(individual.ranges().len(), 4);
+
+        let pairs = SeedSpec::new(values.clone(), SeedKind::StartLength).unwrap();
+        assert_eq!(pairs.seeds().count(), 27);
+        assert_eq!(pairs.values(), values);
+
+        let inclusive =
+            SeedSpec::<u64>::new(vec![79, 92, 55, 67], SeedKind::InclusiveStartEnd).unwrap();
+        assert_eq!(inclusive.ranges(), pairs.ranges());
+        assert_eq!(inclusive.values(), vec![79, 92, 55, 67]);
+
+        let at_max = SeedSpec::new(vec![u64::MAX - 1, 2], SeedKind::StartLength).unwrap();
+        assert_eq!(
+            at_max.seeds().collect::<Vec<_>>(),
+            vec![u64::MAX - 1, u64::MAX]
+        );
+    }
+
+    #[test]
+    fn test_seed_spec_errors() {
+        assert_eq!(
+            SeedSpec::<u64>::new(vec![1, 2, 3], SeedKind::StartLength),
+            Err(SeedError::OddPairList(3))
+        );
+        assert_eq!(
+            SeedSpec::<u64>::new(vec![1, 2, 3], SeedKind::InclusiveStartEnd),
+            Err(SeedError::OddPairList(3))
+        );
+        assert_eq!(
+            SeedSpec::<u64>::new(vec![5, 0], SeedKind::StartLength),
+            Err(SeedError::EmptyRange { start: 5 })
+        );
+        assert_eq!(
+            SeedSpec::<u64>::new(vec![5, 4], SeedKind::InclusiveStartEnd),
+            Err(SeedError::EndBeforeStart { start: 5, end: 4 })
+        );
+        assert_eq!(
+            SeedSpec::new(vec![u64::MAX, 2], SeedKind::StartLength),
+            Err(SeedError::Overflow {
+                start: u64::MAX,
+                length: 2
+            })
+        );
+        assert_eq!(
+            SeedSpec::new(vec![0, u64::MAX], SeedKind::InclusiveStartEnd),
+            Err(SeedError::Overflow {
+                start: 0,
+                length: u64::MAX
+            })
+        );
+        assert!(SeedSpec::<u64>::new(vec![1, 2, 3], SeedKind::Individual).is_ok());
+    }
+}
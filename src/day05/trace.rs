@@ -0,0 +1,270 @@
+use super::{parse_input, Almanac, CategoryId, MapRange, SeedKind, SeedRange, SeedSpec};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct RangeMatch {
+    // position in the map as written
+    pub(super) index: usize,
+    pub(super) line: usize,
+    pub(super) range: MapRange,
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) struct TraceStep {
+    pub(super) from: String,
+    pub(super) to: String,
+    pub(super) input: u64,
+    pub(super) output: u64,
+    pub(super) matched: Option<RangeMatch>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) struct SeedTrace {
+    pub(super) seed: u64,
+    pub(super) steps: Vec<TraceStep>,
+}
+impl SeedTrace {
+    pub(super) fn location(&self) -> u64 {
+        self.steps.last().map_or(self.seed, |step| step.output)
+    }
+}
+
+// One node per range in a category; `children` are the splits produced by
+// the map leaving that category.
+#[derive(Debug, PartialEq)]
+pub(super) struct RangeTrace {
+    pub(super) category: String,
+    pub(super) range: SeedRange,
+    pub(super) matched: Option<RangeMatch>,
+    pub(super) children: Vec<RangeTrace>,
+}
+
+impl Almanac {
+    pub(super) fn explain_seed(&self, seed: u64) -> SeedTrace {
+        let mut steps = Vec::new();
+        let mut current = seed;
+        for (from, map) in self.chain() {
+            let (output, matched) = match map.find(current) {
+                Some((index, converted)) => (
+                    converted,
+                    Some(RangeMatch {
+                        index: map.range_indices[index],
+                        line: map.range_lines[index],
+                        range: map.ranges[index],
+                    }),
+                ),
+                None => (current, None),
+            };
+            steps.push(TraceStep {
+                from: self.name(from).to_string(),
+                to: self.name(map.to).to_string(),
+                input: current,
+                output,
+                matched,
+            });
+            current = output;
+        }
+        SeedTrace { seed, steps }
+    }
+
+    pub(super) fn explain_range(&self, seed_range: SeedRange) -> RangeTrace {
+        match self.categories.get("seed") {
+            Some(seed) => self.explain_range_from(seed, seed_range, None),
+            None => RangeTrace {
+                category: "seed".to_string(),
+                range: seed_range,
+                matched: None,
+                children: Vec::new(),
+            },
+        }
+    }
+
+    fn explain_range_from(
+        &self,
+        category: CategoryId,
+        range: SeedRange,
+        matched: Option<RangeMatch>,
+    ) -> RangeTrace {
+        let children = match self.map_from(category) {
+            Some(map) => map
+                .split_range(range)
+                .into_iter()
+                .map(|(split, index)| {
+                    let matched = index.map(|index| RangeMatch {
+                        index: map.range_indices[index],
+                        line: map.range_lines[index],
+                        range: map.ranges[index],
+                    });
+                    self.explain_range_from(map.to, split, matched)
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        RangeTrace {
+            category: self.name(category).to_string(),
+            range,
+            matched,
+            children,
+        }
+    }
+}
+
+pub(super) fn print_traces(input: &str, args: &[String]) {
+    let almanac = match parse_input(input, SeedKind::Individual) {
+        Ok(almanac) => almanac,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    let seeds: Vec<u64> = if args.is_empty() {
+        almanac.seeds.values()
+    } else {
+        match args.iter().map(|arg| arg.parse::<u64>()).collect() {
+            Ok(seeds) => seeds,
+            Err(e) => {
+                eprintln!("could not parse seeds: {e}");
+                return;
+            }
+        }
+    };
+
+    for seed in &seeds {
+        print!("{}", format_seed_trace(&almanac.explain_seed(*seed)));
+    }
+
+    if args.is_empty() {
+        // the same seed line read as pairs, for the range traces
+        match SeedSpec::new(seeds, SeedKind::StartLength) {
+            Ok(pairs) => {
+                for seed_range in pairs.ranges() {
+                    print!("{}", format_range_trace(&almanac.explain_range(seed_range)));
+                }
+            }
+            Err(e) => eprintln!("invalid seeds: {e}"),
+        }
+    }
+}
+
+fn format_match(matched: &Option<RangeMatch>) -> String {
+    match matched {
+        Some(m) => format!(
+            "range #{} (line {}: {} {} {})",
+            m.index, m.line, m.range.dest, m.range.source, m.range.length
+        ),
+        None => "no range, unchanged".to_string(),
+    }
+}
+
+fn format_seed_trace(trace: &SeedTrace) -> String {
+    let mut out = format!("seed {}\n", trace.seed);
+    for step in &trace.steps {
+        out += &format!(
+            "  {} {} -> {} {} via {}\n",
+            step.from,
+            step.input,
+            step.to,
+            step.output,
+            format_match(&step.matched)
+        );
+    }
+    out += &format!("  location: {}\n", trace.location());
+    out
+}
+
+fn format_range_trace(trace: &RangeTrace) -> String {
+    let mut out = String::new();
+    format_range_node(trace, 0, &mut out);
+    out
+}
+
+fn format_range_node(node: &RangeTrace, depth: usize, out: &mut String) {
+    let indent = "  ".repeat(depth);
+    out.push_str(&format!(
+        "{indent}{} [{}..={}]",
+        node.category,
+        node.range.start,
+        node.range.last()
+    ));
+    if depth > 0 {
+        out.push_str(&format!(" via {}", format_match(&node.matched)));
+    }
+    out.push('\n');
+    for child in &node.children {
+        format_range_node(child, depth + 1, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day05::tests::EXAMPLE_INPUT;
+
+    #[test]
+    fn test_explain_seed() {
+        let almanac = parse_input(EXAMPLE_INPUT, SeedKind::Individual).unwrap();
+        let trace = almanac.explain_seed(79);
+
+        let outputs: Vec<u64> = trace.steps.iter().map(|s| s.output).collect();
+        assert_eq!(outputs, vec![81, 81, 81, 74, 78, 78, 82]);
+        assert_eq!(trace.location(), 82);
+
+        let first = &trace.steps[0];
+        assert_eq!(first.from, "seed");
+        assert_eq!(first.to, "soil");
+        let matched = first.matched.unwrap();
+        assert_eq!(matched.index, 1);
+        assert_eq!(matched.line, 5);
+        assert_eq!(trace.steps[1].matched, None);
+    }
+
+    #[test]
+    fn test_explain_seed_matches_locations() {
+        let almanac = parse_input(EXAMPLE_INPUT, SeedKind::Individual).unwrap();
+        let locations: Vec<u64> = almanac
+            .seeds
+            .seeds()
+            .map(|seed| almanac.explain_seed(seed).location())
+            .collect();
+        assert_eq!(locations, almanac.get_locations());
+    }
+
+    fn leaves(node: &RangeTrace, out: &mut Vec<SeedRange>) {
+        if node.children.is_empty() {
+            out.push(node.range);
+        }
+        for child in &node.children {
+            leaves(child, out);
+        }
+    }
+
+    #[test]
+    fn test_explain_range_leaves() {
+        let almanac = parse_input(EXAMPLE_INPUT, SeedKind::StartLength).unwrap();
+        let trace = almanac.explain_range(SeedRange {
+            start: 82,
+            length: 1,
+        });
+        let mut found = Vec::new();
+        leaves(&trace, &mut found);
+        assert_eq!(
+            found,
+            vec![SeedRange {
+                start: 46,
+                length: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn test_explain_range_minimum() {
+        let almanac = parse_input(EXAMPLE_INPUT, SeedKind::StartLength).unwrap();
+        let mut found = Vec::new();
+        for seed_range in almanac.seeds.ranges() {
+            let trace = almanac.explain_range(seed_range);
+            assert_eq!(trace.category, "seed");
+            leaves(&trace, &mut found);
+        }
+        let min = found.iter().map(|sr| sr.start).min().unwrap();
+        assert_eq!(min, 46);
+    }
+}
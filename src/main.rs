@@ -1,8 +1,22 @@
+mod bench;
+mod cli;
 mod day01;
 mod day02;
 mod day03;
 mod day05;
+mod rng;
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None => run_all(),
+        Some("day01") => day01::run(&args[1..]),
+        Some("day02") => day02::run(&args[1..]),
+        Some("day05") => day05::run(&args[1..]),
+        Some(other) => eprintln!("unknown day: {other}"),
+    }
+}
+
+fn run_all() {
     println!("*** day 01 ***");
     day01::main();
     println!("*** day 02 ***");
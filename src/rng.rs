@@ -0,0 +1,36 @@
+use std::ops::{Range, RangeInclusive};
+
+// SplitMix64: tiny, std-only and reproducible from a single seed
+#[derive(Debug, Clone)]
+pub(crate) struct Rng {
+    state: u64,
+}
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub(crate) fn gen_range(&mut self, range: Range<u64>) -> u64 {
+        assert!(range.start < range.end, "empty range {range:?}");
+        let span = range.end - range.start;
+        range.start + self.next_u64() % span
+    }
+
+    pub(crate) fn gen_range_inclusive(&mut self, range: RangeInclusive<u64>) -> u64 {
+        let (start, end) = range.into_inner();
+        assert!(start <= end, "empty range {start}..={end}");
+        match (end - start).checked_add(1) {
+            Some(span) => start + self.next_u64() % span,
+            // the full u64 domain
+            None => self.next_u64(),
+        }
+    }
+}